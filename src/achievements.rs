@@ -1,18 +1,32 @@
-// savedata: Save data structs and impl methods.
+// achievements: Achievement progress, evaluated from the save data.
+//
+// Every achievement is listed in REGISTRY, along with the name Steam knows it
+// by and how to work out its state from a save game.
+use crate::bosses::Bosses;
+use crate::clock::{
+    frames_to_duration,
+    Clock,
+};
+use crate::passwords;
 use crate::savedata::{
     Creature,
     THItemType,
     THSaveData,
 };
-use humantime::{
-    format_duration,
-    FormattedDuration,
+use crate::steam::SteamAchievements;
+use anyhow::{
+    anyhow,
+    Result,
 };
+use humantime::format_rfc3339;
 use std::collections::HashSet;
 use std::fmt;
 use std::iter::FromIterator;
 use std::str;
-use std::time::Duration;
+use std::time::{
+    Duration,
+    UNIX_EPOCH,
+};
 
 const ACHIEVEMENT_ALL_HEALTH: i32 = 13;
 const ACHIEVEMENT_ALL_NOTES: i32 = 28;
@@ -23,64 +37,422 @@ const ACHIEVEMENT_ALL_TOOLS: i32 = 16;
 const ACHIEVEMENT_ALL_WEAPONS: i32 = 20;
 const ACHIEVEMENT_BRICK_BREAKER: i32 = 2_000;
 const ACHIEVEMENT_BUBBLE_BREAKER: i32 = 2_000;
+pub const ACHIEVEMENT_LOW_PERCENT: f32 = 40.0;
 const FRAGMENTS_PER_NODE: usize = 5;
 
 // Overclocked needs to be done in under 4 hours.
 // Calculated as 4 * 60 * 60 / 0.01667 and rounding up to the next whole frame.
-const OVERCLOCKED_MAX_FRAMES: f64 = 863_828.0;
-
-// Bosses that we can check for in the save file. We can't check for Athetos,
-// since the game doesn't save after defeating him.
-const BOSSES: &[&str] = &[
-    "Xedur",
-    "Telal",
-    "Uruku",
-    "Gir-Tab",
-    "Vision",
-    "Clone",
-    "Ukhu",
-    "Sentinel",
-];
+pub const OVERCLOCKED_MAX_FRAMES: f64 = 863_828.0;
+
+// Speedrunner needs to be done in under 2 hours, calculated as above.
+pub const SPEEDRUNNER_MAX_FRAMES: f64 = 431_914.0;
 
-// Axiom verge time is based on 60fps with a 16.67ms frame time.
-const FRAMETIME_MS: f64 = 0.01667;
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AchievementStatus {
+    // Earned, according to the save data.
+    Unlocked,
+
+    // Not earned yet, but still possible with this save.
+    Locked,
+
+    // Can no longer be earned with this save.
+    Failed,
+
+    // The save data doesn't record enough to tell.
+    Unknown,
+}
 
-// Turns frame counts into real time duration.
-fn frames_to_duration(frames: f64) -> FormattedDuration {
-    let seconds = (frames * FRAMETIME_MS) as u64;
-    let duration = Duration::from_secs(seconds);
+impl fmt::Display for AchievementStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::Unlocked => "Unlocked",
+            Self::Locked   => "Locked",
+            Self::Failed   => "Failed",
+            Self::Unknown  => "Unknown",
+        };
 
-    format_duration(duration)
+        write!(f, "{}", desc)
+    }
 }
 
+// The state of an achievement in a save, along with a description of the
+// progress towards it.
 #[derive(Debug)]
-enum BossState {
-    Alive,
-    Dead,
+pub struct Evaluation {
+    pub status: AchievementStatus,
+    pub progress: String,
+
+    // Whether the achievement's condition holds. Conditions on finishing the
+    // game, such as staying under a time limit, hold while they're locked.
+    holds: bool,
 }
 
-impl From<bool> for BossState {
-    fn from(dead: bool) -> Self {
-        if dead {
-            Self::Dead
+impl Evaluation {
+    fn new(status: AchievementStatus, progress: String) -> Self {
+        Self {
+            status,
+            progress,
+            holds: status == AchievementStatus::Unlocked,
+        }
+    }
+
+    pub fn holds(&self) -> bool {
+        self.holds
+    }
+
+    // Progress towards a count, unlocked once the count is reached.
+    fn counted<T>(current: T, needed: T, unit: &str) -> Self
+    where
+        T: Copy + Into<f64> + PartialOrd + fmt::Display,
+    {
+        let percent = current.into() / needed.into() * 100.0;
+        let status = if current >= needed {
+            AchievementStatus::Unlocked
+        }
+        else {
+            AchievementStatus::Locked
+        };
+
+        let progress = if unit.is_empty() {
+            format!("{}/{} ({:.2}%)", current, needed, percent)
         }
         else {
-            Self::Alive
+            format!("{}/{} {} ({:.2}%)", current, needed, unit, percent)
+        };
+
+        Self::new(status, progress)
+    }
+
+    // Achievements for finishing the game under some limit. The save can't
+    // tell us if the game has been finished, so these stay locked until the
+    // limit is broken.
+    fn limited(within_limit: bool, progress: String) -> Self {
+        let status = if within_limit {
+            AchievementStatus::Locked
+        }
+        else {
+            AchievementStatus::Failed
+        };
+
+        Self {
+            holds: within_limit,
+            ..Self::new(status, progress)
         }
     }
 }
 
-impl fmt::Display for BossState {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let desc = match self {
-            Self::Alive => "Alive",
-            Self::Dead  => "Dead",
+pub trait Achievement: Sync {
+    // Name of the achievement in the Steam API and Steam's stats files.
+    fn api_name(&self) -> &'static str;
+
+    // Name of the achievement as shown on Steam.
+    fn name(&self) -> &'static str;
+
+    // What needs to be done to unlock the achievement.
+    fn description(&self) -> &'static str;
+
+    fn evaluate(&self, savedata: &THSaveData) -> Evaluation;
+
+    // ID used to pick the achievement on the command line, the API name
+    // without its prefix, eg. low_percent for ACH_LOW_PERCENT.
+    fn id(&self) -> String {
+        self.api_name()
+            .trim_start_matches("ACH_")
+            .to_lowercase()
+    }
+}
+
+// Finds an achievement in the registry by its ID.
+pub fn find(id: &str) -> Result<&'static dyn Achievement> {
+    let id = id.to_lowercase();
+
+    REGISTRY
+        .iter()
+        .find(|achievement| achievement.id() == id)
+        .copied()
+        .ok_or_else(|| {
+            let ids: Vec<String> = REGISTRY
+                .iter()
+                .map(|achievement| achievement.id())
+                .collect();
+
+            anyhow!(
+                "Unknown achievement: {}, expected one of: {}",
+                id,
+                ids.join(", "),
+            )
+        })
+}
+
+// An achievement evaluated by one of the checks on Achievements.
+struct Check {
+    api_name: &'static str,
+    name: &'static str,
+    description: &'static str,
+    evaluator: fn(&Achievements) -> Evaluation,
+}
+
+impl Achievement for Check {
+    fn api_name(&self) -> &'static str {
+        self.api_name
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn evaluate(&self, savedata: &THSaveData) -> Evaluation {
+        (self.evaluator)(&Achievements::new(savedata))
+    }
+}
+
+// Boss kill achievements, checked for using every sign of the boss being
+// defeated in the save data.
+struct BossKill {
+    api_name: &'static str,
+    name: &'static str,
+    description: &'static str,
+
+    // Name of the boss in the save data.
+    boss: &'static str,
+}
+
+impl Achievement for BossKill {
+    fn api_name(&self) -> &'static str {
+        self.api_name
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn evaluate(&self, savedata: &THSaveData) -> Evaluation {
+        // Later progress only suggests a kill, so it's shown in the progress
+        // but doesn't unlock the achievement.
+        let state = Bosses::new(savedata).state(self.boss);
+        let status = if state.is_certain() {
+            AchievementStatus::Unlocked
+        }
+        else {
+            AchievementStatus::Locked
         };
 
-        write!(f, "{}", desc)
+        Evaluation::new(status, state.to_string())
     }
 }
 
+// Every Steam achievement for the game, in the order Steam lists them. The
+// API names follow the pattern of the ones seen in Steam's stats files, but
+// haven't all been checked against the published schema, so Steam's
+// achievements are also matched by name.
+pub static REGISTRY: &[&dyn Achievement] = &[
+    &Check {
+        api_name: "ACH_100_HEALTH",
+        name: "100% Health",
+        description: "Collect every Health Node",
+        evaluator: |a| a.all_health(),
+    },
+    &Check {
+        api_name: "ACH_100_ITEMS",
+        name: "100% Items",
+        description: "Collect every item",
+        evaluator: |a| a.all_items(),
+    },
+    &Check {
+        api_name: "ACH_100_MAP",
+        name: "100% Map",
+        description: "Explore every screen",
+        evaluator: |a| a.all_map(),
+    },
+    &Check {
+        api_name: "ACH_100_NOTES",
+        name: "100% Notes",
+        description: "Collect every note",
+        evaluator: |a| a.all_notes(),
+    },
+    &Check {
+        api_name: "ACH_100_POWER",
+        name: "100% Power",
+        description: "Collect every Power Node",
+        evaluator: |a| a.all_power(),
+    },
+    &Check {
+        api_name: "ACH_100_TOOLS",
+        name: "100% Tools",
+        description: "Collect every tool",
+        evaluator: |a| a.all_tools(),
+    },
+    &Check {
+        api_name: "ACH_100_WEAPONS",
+        name: "100% Weapons",
+        description: "Collect every weapon",
+        evaluator: |a| a.all_weapons(),
+    },
+    &Check {
+        api_name: "ACH_25_ITEMS",
+        name: "25% Items",
+        description: "Collect 25% of the items",
+        evaluator: |a| a.quarter_items(),
+    },
+    &Check {
+        api_name: "ACH_50_ITEMS",
+        name: "50% Items",
+        description: "Collect 50% of the items",
+        evaluator: |a| a.half_items(),
+    },
+    &Check {
+        api_name: "ACH_75_ITEMS",
+        name: "75% Items",
+        description: "Collect 75% of the items",
+        evaluator: |a| a.three_quarter_items(),
+    },
+    &Check {
+        api_name: "ACH_BRICK_BREAKER",
+        name: "Brick Breaker",
+        description: "Destroy 2,000 bricks",
+        evaluator: |a| a.brick_breaker(),
+    },
+    &Check {
+        api_name: "ACH_BUBBLE_BREAKER",
+        name: "Bubble Breaker",
+        description: "Destroy 2,000 red goo bubbles",
+        evaluator: |a| a.bubble_breaker(),
+    },
+    &Check {
+        api_name: "ACH_HACK",
+        name: "Hack",
+        description: "Glitch a creature with the Address Disruptor",
+        evaluator: |a| a.hack(),
+    },
+    &Check {
+        api_name: "ACH_HACKER",
+        name: "Hacker",
+        description: "Glitch every creature in the bestiary",
+        evaluator: |a| a.hacker(),
+    },
+    &Check {
+        api_name: "ACH_INVINCIBLE",
+        name: "Invincible",
+        description: "Finish the game without dying",
+        evaluator: |a| a.invincible(),
+    },
+    &Check {
+        api_name: "ACH_JUSTIN_BAILEY",
+        name: "Justin Bailey",
+        description: "Enter the JUSTIN BAILEY password",
+        evaluator: |a| a.justin_bailey(),
+    },
+    &Check {
+        api_name: "ACH_LOW_PERCENT",
+        name: "Low %",
+        description: "Finish the game with 40% of the items or fewer",
+        evaluator: |a| a.low_percent(),
+    },
+    &Check {
+        api_name: "ACH_MOSTLY_INVINCIBLE",
+        name: "Mostly Invincible",
+        description: "Finish the game, dying no more than once",
+        evaluator: |a| a.mostly_invincible(),
+    },
+    &Check {
+        api_name: "ACH_OVERCLOCKED",
+        name: "Overclocked",
+        description: "Finish the game in under 4 hours of game time",
+        evaluator: |a| a.overclocked(),
+    },
+    &Check {
+        api_name: "ACH_PACIFIST",
+        name: "Pacifist",
+        description: "Finish the game without killing the Clone",
+        evaluator: |a| a.pacifist(),
+    },
+    &Check {
+        api_name: "ACH_PASSWORD",
+        name: "Password",
+        description: "Enter a password on the password screen",
+        evaluator: |a| a.password(),
+    },
+    &Check {
+        api_name: "ACH_SPEEDRUNNER",
+        name: "Speedrunner",
+        description: "Finish the game in under 2 hours of game time",
+        evaluator: |a| a.speedrunner(),
+    },
+    &BossKill {
+        api_name: "ACH_XEDUR",
+        name: "Xedur",
+        description: "Defeat Xedur",
+        boss: "Xedur",
+    },
+    &BossKill {
+        api_name: "ACH_TELAL",
+        name: "Telal",
+        description: "Defeat Telal",
+        boss: "Telal",
+    },
+    &BossKill {
+        api_name: "ACH_URUKU",
+        name: "Uruku",
+        description: "Defeat Uruku",
+        boss: "Uruku",
+    },
+    &BossKill {
+        api_name: "ACH_GIR_TAB",
+        name: "Gir-Tab",
+        description: "Defeat Gir-Tab",
+        boss: "Gir-Tab",
+    },
+    // Vision is actually called Hallucination for the achievement
+    &BossKill {
+        api_name: "ACH_HALLUCINATION",
+        name: "Hallucination",
+        description: "Defeat the Hallucination",
+        boss: "Vision",
+    },
+    &BossKill {
+        api_name: "ACH_CLONE",
+        name: "Clone",
+        description: "Defeat the Clone",
+        boss: "Clone",
+    },
+    &BossKill {
+        api_name: "ACH_UKHU",
+        name: "Ukhu",
+        description: "Defeat Ukhu",
+        boss: "Ukhu",
+    },
+    &BossKill {
+        api_name: "ACH_SENTINEL",
+        name: "Sentinel",
+        description: "Defeat the Sentinel",
+        boss: "Sentinel",
+    },
+    &Check {
+        api_name: "ACH_ATHETOS",
+        name: "Athetos",
+        description: "Defeat Athetos and finish the game",
+        evaluator: |a| a.athetos(),
+    },
+];
+
+// Prints the description, ID and Steam API name of an achievement.
+fn print_details(achievement: &dyn Achievement) {
+    println!(
+        "    {} [{}, {}]",
+        achievement.description(),
+        achievement.id(),
+        achievement.api_name(),
+    );
+}
+
 pub struct Achievements<'a> {
     savedata: &'a THSaveData,
 }
@@ -88,30 +460,11 @@ pub struct Achievements<'a> {
 impl<'a> Achievements<'a> {
     pub fn new(savedata: &'a THSaveData) -> Self {
         Self {
-            savedata: savedata,
+            savedata,
         }
     }
 
     // Helper methods for achievements
-    fn boss_state(&self, boss: &str) -> BossState {
-        // Boss states are recorded in the speecrun_checkpoints even when we're
-        // not on a speedrun. We can use this to check for bossing being
-        // killed.
-        let checkpoints = &self.savedata.speedrun_checkpoints;
-        let state = if let Some(checkpoints) = checkpoints {
-            // If we find the boss, it's dead.
-            checkpoints
-                .iter()
-                .filter(|c| c.name == boss)
-                .count() > 0
-        }
-        else {
-            false
-        };
-
-        state.into()
-    }
-
     fn item_type_count(&self, type_: THItemType) -> usize {
         // Get a count for specific item types. Used to check for various 100%
         // achievements.
@@ -121,7 +474,9 @@ impl<'a> Achievements<'a> {
             .count()
     }
 
-    fn item_counts(&self) -> (usize, i32, f32) {
+    // Items collected and needed for the item percentage, along with the
+    // percentage itself.
+    pub fn item_counts(&self) -> (usize, i32, f32) {
         let needed = {
             ACHIEVEMENT_ALL_HEALTH
             + ACHIEVEMENT_ALL_NOTES
@@ -161,288 +516,396 @@ impl<'a> Achievements<'a> {
         (current, needed, percent)
     }
 
+    // Number of items collected, against the number needed to reach the
+    // given percentage.
+    fn items_tier(&self, tier: f32) -> Evaluation {
+        let (current, needed, _) = self.item_counts();
+        let needed = (needed as f32 * tier / 100.0).ceil() as u32;
+
+        Evaluation::counted(current as u32, needed, "items")
+    }
+
     // 100% Health achievement
     // The count here also includes node fragments
-    fn all_health(&self) {
+    fn all_health(&self) -> Evaluation {
         let needed = ACHIEVEMENT_ALL_HEALTH;
         let nodes = self.item_type_count(THItemType::HealthNode);
         let frags = self.item_type_count(THItemType::HealthNodeFragment);
         let frags = frags / FRAGMENTS_PER_NODE;
-        let current = nodes + frags;
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
+        let current = (nodes + frags) as i32;
 
-        println!(
-            "  - 100% Health: {}/{} ({:.2}%)",
-            current,
-            needed,
-            percent,
-        );
+        Evaluation::counted(current, needed, "")
     }
 
     // 100% Items
-    fn all_items(&self) {
-        let (current, needed, percent) = self.item_counts();
+    fn all_items(&self) -> Evaluation {
+        let (current, needed, _) = self.item_counts();
 
-        println!(
-            "  - 100% Items: {}/{} ({:.2}%)",
-            current,
-            needed,
-            percent,
-        );
+        Evaluation::counted(current as i32, needed, "")
     }
 
     // 100% Map achievement
-    fn all_map(&self) {
+    fn all_map(&self) -> Evaluation {
         let needed = self.savedata.total_screen_count;
         let current = self.savedata.screen_count;
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
 
-        println!(
-            "  - 100% Map: {}/{} screens ({:.2}%)",
-            current, needed, percent,
-        );
+        Evaluation::counted(current, needed, "screens")
     }
 
     // 100% Notes
-    fn all_notes(&self) {
+    fn all_notes(&self) -> Evaluation {
         let needed = ACHIEVEMENT_ALL_NOTES;
-        let current = self.item_type_count(THItemType::Lore);
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
+        let current = self.item_type_count(THItemType::Lore) as i32;
 
-        println!(
-            "  - 100% Notes: {}/{} ({:.2}%)",
-            current,
-            needed,
-            percent,
-        );
+        Evaluation::counted(current, needed, "")
     }
 
     // 100% Power
-    fn all_power(&self) {
+    fn all_power(&self) -> Evaluation {
         let needed = ACHIEVEMENT_ALL_POWER;
         let nodes = self.item_type_count(THItemType::PowerNode);
         let frags = self.item_type_count(THItemType::PowerNodeFragment);
         let frags = frags / FRAGMENTS_PER_NODE;
-        let current = nodes + frags;
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
+        let current = (nodes + frags) as i32;
 
-        println!(
-            "  - 100% Power: {}/{} ({:.2}%)",
-            current,
-            needed,
-            percent,
-        );
+        Evaluation::counted(current, needed, "")
     }
 
     // 100% Tools
-    fn all_tools(&self) {
+    fn all_tools(&self) -> Evaluation {
         let needed = ACHIEVEMENT_ALL_TOOLS;
         let tools = self.item_type_count(THItemType::Tool);
         let upgrades = self.item_type_count(THItemType::PermanentUpgrade);
-        let current = tools + upgrades;
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
+        let current = (tools + upgrades) as i32;
 
-        println!(
-            "  - 100% Tools: {}/{} ({:.2}%)",
-            current,
-            needed,
-            percent,
-        );
+        Evaluation::counted(current, needed, "")
     }
 
     // 100% Weapons
-    fn all_weapons(&self) {
+    fn all_weapons(&self) -> Evaluation {
         let needed = ACHIEVEMENT_ALL_WEAPONS;
-        let current = self.item_type_count(THItemType::Weapon);
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
+        let current = self.item_type_count(THItemType::Weapon) as i32;
 
-        println!(
-            "  - 100% Weapons: {}/{} ({:.2}%)",
-            current,
-            needed,
-            percent,
-        );
+        Evaluation::counted(current, needed, "")
+    }
+
+    // Item percentage achievements
+    fn quarter_items(&self) -> Evaluation {
+        self.items_tier(25.0)
     }
 
-    // Boss kill achievements
-    fn boss(&self, boss: &str) {
-        let state = self.boss_state(boss);
+    fn half_items(&self) -> Evaluation {
+        self.items_tier(50.0)
+    }
 
-        // Vision is actually called Hallucination for the achievement
-        let boss = match boss {
-            "Vision" => "Hallucination",
-            _        => boss,
-        };
+    fn three_quarter_items(&self) -> Evaluation {
+        self.items_tier(75.0)
+    }
 
-        println!("  - {}: {}", boss, state);
+    // The game doesn't save after Athetos is defeated.
+    fn athetos(&self) -> Evaluation {
+        let progress = "Not recorded in the save data".to_string();
+
+        Evaluation::new(AchievementStatus::Unknown, progress)
     }
 
     // Brick Breaker achievement
-    fn brick_breaker(&self) {
+    fn brick_breaker(&self) -> Evaluation {
         let needed = ACHIEVEMENT_BRICK_BREAKER;
         let current = self.savedata.bricks_destroyed.clamp(0, needed);
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
 
-        println!(
-            "  - Brick Breaker: {}/{} ({:.2}%)",
-            current, needed, percent,
-        );
+        Evaluation::counted(current, needed, "")
     }
 
     // Bubble Breaker achievement
-    fn bubble_breaker(&self) {
+    fn bubble_breaker(&self) -> Evaluation {
         let needed = ACHIEVEMENT_BUBBLE_BREAKER;
         let current = self.savedata.red_goo_destroyed.clamp(0, needed);
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
 
-        println!(
-            "  - Bubble Breaker: {}/{} ({:.2}%)",
-            current, needed, percent,
-        );
+        Evaluation::counted(current, needed, "")
     }
 
-    fn hack(&self) {
+    // Any creature counts, not just the ones Hacker needs. Unknown creatures
+    // are left out, since we can't tell if they're really creatures.
+    fn hack(&self) -> Evaluation {
         let needed = 1;
         let glitched = &self.savedata.creatures_glitched;
         let current = if let Some(glitched) = glitched {
-            glitched.len().clamp(0, needed)
+            glitched
+                .iter()
+                .filter(|creature| !matches!(creature, Creature::Unknown(_)))
+                .count()
+                .clamp(0, needed)
         }
         else {
             0
         };
 
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
-
-        println!(
-            "  - Hack: {}/{} ({:.2}%)",
-            current, needed, percent,
-        );
+        Evaluation::counted(current as u32, needed as u32, "")
     }
 
-    fn hacker(&self) {
+    fn hacker(&self) -> Evaluation {
         let needed = Creature::achievement_list().len();
         let glitched = &self.savedata.creatures_glitched;
         let current = if let Some(glitched) = glitched {
-            glitched.len()
+            glitched.iter().filter(|creature| creature.hacker()).count()
         }
         else {
             0
         };
 
-        let percent: f32 = current as f32 / needed as f32 * 100.0;
-
-        println!(
-            "  - Hacker: {}/{} ({:.2}%)",
-            current, needed, percent,
-        );
+        Evaluation::counted(current as u32, needed as u32, "")
     }
 
-    fn low_percent(&self) {
-        let maximum = 40.0;
-        let (current, needed, percent) = self.item_counts();
+    // Finishing the game with at most the given number of deaths.
+    fn death_limit(&self, maximum: i32) -> Evaluation {
+        let current = self.savedata.num_deaths;
 
-        let state = if percent >= maximum {
-            "Failed"
+        let maybe_plural = if current == 1 {
+            "death"
         }
         else {
-            "OK"
+            "deaths"
         };
 
-        println!(
-            "  - Low %: {}/{} ({:.2}%) ({})",
-            current, needed, percent, state,
-        );
+        let progress = format!("{}/{} {}", current, maximum, maybe_plural);
+
+        Evaluation::limited(current <= maximum, progress)
     }
 
-    fn mostly_invincible(&self) {
-        let maximum = 1;
-        let current = self.savedata.num_deaths;
+    fn invincible(&self) -> Evaluation {
+        self.death_limit(0)
+    }
 
-        let state = if current > maximum {
-            "Failed"
-        }
-        else {
-            "OK"
-        };
+    fn justin_bailey(&self) -> Evaluation {
+        let entered = self.savedata.justin_bailey
+            || passwords::is_entered(self.savedata, "JUSTIN BAILEY");
 
-        let maybe_plural = if current == 1 {
-            "death"
+        let (status, progress) = if entered {
+            (AchievementStatus::Unlocked, "Password entered")
         }
         else {
-            "deaths"
+            (AchievementStatus::Locked, "Password not entered")
         };
 
-        println!(
-            "  - Mostly Invincible: {}/{} {} ({})",
-            current, maximum, maybe_plural, state,
-        );
+        Evaluation::new(status, progress.to_string())
+    }
+
+    fn low_percent(&self) -> Evaluation {
+        let (current, needed, percent) = self.item_counts();
+        let progress = format!("{}/{} ({:.2}%)", current, needed, percent);
+
+        Evaluation::limited(percent < ACHIEVEMENT_LOW_PERCENT, progress)
+    }
+
+    fn mostly_invincible(&self) -> Evaluation {
+        self.death_limit(1)
     }
 
-    fn overclocked(&self) {
-        let frames = self.savedata.effective_frames;
-        let current_time = frames_to_duration(frames as f64);
-        let max_time = frames_to_duration(OVERCLOCKED_MAX_FRAMES);
+    // Finishing the game within the given number of frames. The limit is
+    // checked against game time, real time is shown alongside it.
+    fn time_limit(&self, max_frames: f64) -> Evaluation {
+        let frames = Clock::Game.frames(self.savedata);
+        let current_time = frames_to_duration(frames);
+        let max_time = frames_to_duration(max_frames);
+        let real_time = frames_to_duration(Clock::Real.frames(self.savedata));
 
         // Compare the underlying Durations with get_ref
-        let state = if current_time.get_ref() < max_time.get_ref() {
-            "OK"
+        let within = current_time.get_ref() < max_time.get_ref();
+        let progress = format!(
+            "{}/{} {} ({} {})",
+            current_time,
+            max_time,
+            Clock::Game,
+            real_time,
+            Clock::Real,
+        );
+
+        Evaluation::limited(within, progress)
+    }
+
+    fn overclocked(&self) -> Evaluation {
+        self.time_limit(OVERCLOCKED_MAX_FRAMES)
+    }
+
+    fn speedrunner(&self) -> Evaluation {
+        self.time_limit(SPEEDRUNNER_MAX_FRAMES)
+    }
+
+    fn pacifist(&self) -> Evaluation {
+        let state = Bosses::new(self.savedata).state("Clone");
+        let progress = format!("Clone {}", state);
+
+        Evaluation::limited(!state.is_defeated(), progress)
+    }
+
+    fn password(&self) -> Evaluation {
+        let entered = self.savedata.passwords.len();
+        let progress = format!("{} entered", entered);
+        let status = if entered > 0 {
+            AchievementStatus::Unlocked
         }
         else {
-            "Failed"
+            AchievementStatus::Locked
         };
 
-        println!(
-            "  - Overclocked: {}/{} ({})",
-            current_time, max_time, state,
-        );
+        Evaluation::new(status, progress)
     }
 
-    fn pacifist(&self) {
-        let state = self.boss_state("Clone");
-
-        let ok  = match state {
-            BossState::Alive => "OK",
-            BossState::Dead  => "Failed",
-        };
+    // Warns about glitched creatures that we don't know about. These don't
+    // count towards Hacker, since we can't tell if the game needs them.
+    fn warn_unknown_creatures(&self) {
+        let glitched = self.savedata.creatures_glitched.iter().flatten();
 
-        println!("  - Pacifist: Clone {} ({})", state, ok);
+        for creature in glitched {
+            if let Creature::Unknown(id) = creature {
+                eprintln!("Warning: Unknown glitched creature: {}", id);
+            }
+        }
     }
 
-    pub fn progress(&self) {
+    // Prints the state of every achievement in the registry. Verbose output
+    // includes the description and Steam API name of each achievement.
+    pub fn progress(&self, verbose: bool) {
+        self.warn_unknown_creatures();
+
         println!("Achievement Progress:");
 
-        self.all_health();
-        self.all_items();
-        self.all_map();
-        self.all_notes();
-        self.all_power();
-        self.all_tools();
-        self.all_weapons();
-        self.brick_breaker();
-        self.bubble_breaker();
-        self.hack();
-        self.hacker();
-        self.low_percent();
-        self.mostly_invincible();
-        self.overclocked();
-        self.pacifist();
-
-        for boss in BOSSES {
-            self.boss(boss);
+        for achievement in REGISTRY {
+            let evaluation = achievement.evaluate(self.savedata);
+
+            println!(
+                "  - {}: {} ({})",
+                achievement.name(),
+                evaluation.progress,
+                evaluation.status,
+            );
+
+            if verbose {
+                print_details(*achievement);
+            }
+        }
+    }
+
+    // Prints the state of every achievement in the save and on Steam,
+    // flagging achievements that the save has earned but Steam hasn't
+    // unlocked.
+    pub fn steam_report(&self, steam: &SteamAchievements, verbose: bool) {
+        self.warn_unknown_creatures();
+
+        println!("Steam achievements: {}", steam.filename.display());
+        println!("Achievement Progress (Save / Steam):");
+
+        let mut missing = Vec::new();
+
+        for achievement in REGISTRY {
+            let evaluation = achievement.evaluate(self.savedata);
+            let api_name = steam.api_name_for(
+                achievement.api_name(),
+                achievement.name(),
+            );
+            let steam_status = match api_name {
+                Some(api_name) if steam.is_unlocked(api_name) => {
+                    AchievementStatus::Unlocked
+                },
+                Some(_) => AchievementStatus::Locked,
+                None    => AchievementStatus::Unknown,
+            };
+
+            let flag = if evaluation.status == AchievementStatus::Unlocked
+                && steam_status == AchievementStatus::Locked
+            {
+                missing.push((*achievement, api_name));
+                " [should have unlocked]"
+            }
+            else {
+                ""
+            };
+
+            println!(
+                "  - {}: {} ({} / {}){}",
+                achievement.name(),
+                evaluation.progress,
+                evaluation.status,
+                steam_status,
+                flag,
+            );
+
+            if verbose {
+                print_details(*achievement);
+
+                let api_name = match api_name {
+                    Some(api_name) => api_name,
+                    None           => continue,
+                };
+
+                if api_name != achievement.api_name() {
+                    println!("    Steam API name: {}", api_name);
+                }
+
+                if let Some(time) = steam.unlock_time(api_name) {
+                    let time = UNIX_EPOCH + Duration::from_secs(time as u64);
+                    println!("    Unlocked on Steam {}", format_rfc3339(time));
+                }
+            }
         }
+
+        if missing.is_empty() {
+            return;
+        }
+
+        println!();
+        println!("Should have unlocked on Steam:");
+
+        // Use Steam's API name, which may not be ours if it was matched by
+        // name.
+        for (achievement, api_name) in missing {
+            println!(
+                "  - {} ({})",
+                achievement.name(),
+                api_name.unwrap_or_else(|| achievement.api_name()),
+            );
+        }
+    }
+
+    // Checks that the condition for each achievement holds, printing those
+    // that don't. Returns the number of failed conditions.
+    pub fn check(&self, required: &[&dyn Achievement]) -> usize {
+        let failures: Vec<(&dyn Achievement, Evaluation)> = required
+            .iter()
+            .map(|achievement| {
+                (*achievement, achievement.evaluate(self.savedata))
+            })
+            .filter(|(_, evaluation)| !evaluation.holds())
+            .collect();
+
+        if failures.is_empty() {
+            println!("All {} conditions hold", required.len());
+            return 0;
+        }
+
+        println!("Failed conditions:");
+
+        for (achievement, evaluation) in &failures {
+            println!(
+                "  - {} ({}): {} ({})",
+                achievement.name(),
+                achievement.id(),
+                evaluation.progress,
+                evaluation.status,
+            );
+        }
+
+        failures.len()
     }
 
     pub fn hacker_requires(&self) -> Option<Vec<Creature>> {
         if let Some(glitched) = &self.savedata.creatures_glitched {
-            let needs = Creature::achievement_list();
-            let needs: HashSet<&Creature> = HashSet::from_iter(needs);
             let glitched: HashSet<&Creature> = HashSet::from_iter(glitched);
-            let difference = needs.difference(&glitched);
 
-            // Get an owned Vec<Creature> from our difference.
-            let required: Vec<Creature> = difference
+            let required: Vec<Creature> = Creature::achievement_list()
                 .into_iter()
-                .map(|&&c| c)
+                .filter(|creature| !glitched.contains(creature))
                 .collect();
 
             Some(required)
@@ -451,4 +914,318 @@ impl<'a> Achievements<'a> {
             None
         }
     }
+
+    // Lists the creatures still needed for the Hacker achievement, grouped by
+    // bestiary category.
+    pub fn hacker_report(&self) {
+        self.warn_unknown_creatures();
+
+        let mut remaining = match self.hacker_requires() {
+            Some(remaining) => remaining,
+            None            => {
+                println!("Hacker Achievement requires:");
+                println!("  - All creatures required");
+                return;
+            },
+        };
+
+        let num = remaining.len();
+        let word = if num == 1 {
+            "creature"
+        }
+        else {
+            "creatures"
+        };
+
+        println!("Hacker Achievement requires {} more {}:", num, word);
+
+        if remaining.is_empty() {
+            println!("  - All creatures glitched");
+            return;
+        }
+
+        remaining.sort_by_key(|creature| {
+            (creature.category(), creature.name().to_string())
+        });
+
+        let categories = remaining.chunk_by(|a, b| {
+            a.category() == b.category()
+        });
+
+        for category in categories {
+            println!("  - {}:", category[0].category());
+
+            for creature in category {
+                println!("    - {} ({})", creature, creature.id());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{
+        save_with,
+        savedata,
+    };
+
+    fn boss_kill(boss: &'static str) -> BossKill {
+        BossKill {
+            api_name: "ACH_TEST",
+            name: boss,
+            description: "Defeat a boss",
+            boss,
+        }
+    }
+
+    fn evaluate(id: &str, xml: &str) -> Evaluation {
+        find(id).unwrap().evaluate(&savedata(xml))
+    }
+
+    fn status(id: &str, xml: &str) -> AchievementStatus {
+        evaluate(id, xml).status
+    }
+
+    #[test]
+    fn counts_items_by_type() {
+        let save = save_with(&[]);
+
+        assert_eq!(evaluate("100_weapons", &save).progress, "1/20 (5.00%)");
+        assert_eq!(evaluate("100_tools", &save).progress, "1/16 (6.25%)");
+        assert_eq!(evaluate("100_items", &save).progress, "2/94 (2.13%)");
+        assert_eq!(evaluate("25_items", &save).progress, "2/24 items (8.33%)");
+        assert_eq!(status("100_health", &save), AchievementStatus::Locked);
+    }
+
+    #[test]
+    fn counts_node_fragments_as_whole_nodes() {
+        let fragment = "<THItemRecord><mName>HealthNodeFragment{}</mName>\
+                        <mType>HEALTH_NODE_FRAGMENT</mType>\
+                        <mConsumable>false</mConsumable>\
+                        <mExcludedFromCount>false</mExcludedFromCount>\
+                        </THItemRecord>\n  ";
+        let fragments: String = (0..9)
+            .map(|n| fragment.replace("{}", &n.to_string()))
+            .collect();
+        let xml = save_with(&[(
+            "<KeyPoint>GotAxiomDisruptor",
+            &format!("{}<KeyPoint>GotAxiomDisruptor", fragments),
+        )]);
+
+        assert_eq!(evaluate("100_health", &xml).progress, "1/13 (7.69%)");
+    }
+
+    #[test]
+    fn counts_map_screens() {
+        let save = save_with(&[]);
+
+        assert_eq!(
+            evaluate("100_map", &save).progress,
+            "120/600 screens (20.00%)",
+        );
+    }
+
+    #[test]
+    fn breakers_are_capped_at_the_target() {
+        let save = save_with(&[]);
+
+        assert_eq!(status("brick_breaker", &save), AchievementStatus::Unlocked);
+        assert_eq!(
+            evaluate("brick_breaker", &save).progress,
+            "2000/2000 (100.00%)",
+        );
+        assert_eq!(status("bubble_breaker", &save), AchievementStatus::Locked);
+    }
+
+    #[test]
+    fn hack_counts_any_known_creature() {
+        let jormite = save_with(&[("TrapClaw_Meta", "Jormite")]);
+        let unknown = save_with(&[("TrapClaw_Meta", "NotACreature")]);
+
+        assert_eq!(status("hack", &jormite), AchievementStatus::Unlocked);
+        assert_eq!(status("hack", &unknown), AchievementStatus::Locked);
+    }
+
+    #[test]
+    fn hacker_only_counts_hacker_creatures() {
+        let save = save_with(&[]);
+        let jormite = save_with(&[("TrapClaw_Meta", "Jormite")]);
+        let needed = Creature::achievement_list().len();
+
+        assert_eq!(
+            evaluate("hacker", &save).progress,
+            format!(
+                "1/{} ({:.2}%)",
+                needed,
+                100.0 / needed as f64,
+            ),
+        );
+        assert!(evaluate("hacker", &jormite).progress.starts_with("0/"));
+    }
+
+    #[test]
+    fn death_limits() {
+        let save = save_with(&[]);
+        let deathless = save_with(&[("<mNumDeaths>1", "<mNumDeaths>0")]);
+
+        assert_eq!(evaluate("invincible", &save).progress, "1/0 death");
+        assert_eq!(status("invincible", &save), AchievementStatus::Failed);
+        assert_eq!(status("invincible", &deathless), AchievementStatus::Locked);
+        assert_eq!(
+            evaluate("mostly_invincible", &save).progress,
+            "1/1 death",
+        );
+        assert_eq!(
+            status("mostly_invincible", &save),
+            AchievementStatus::Locked,
+        );
+    }
+
+    #[test]
+    fn time_limits_use_game_time() {
+        // 400000 frames of game time, 432000 of real time. Only real time
+        // is over the Speedrunner limit.
+        let save = save_with(&[]);
+        let slow = save_with(&[(
+            "<mEffectiveFrames>400000",
+            "<mEffectiveFrames>440000",
+        )]);
+
+        assert_eq!(status("speedrunner", &save), AchievementStatus::Locked);
+        assert_eq!(status("speedrunner", &slow), AchievementStatus::Failed);
+        assert_eq!(status("overclocked", &slow), AchievementStatus::Locked);
+    }
+
+    #[test]
+    fn low_percent_fails_at_the_limit() {
+        let record = "<THItemRecord><mName>Lore{}</mName><mType>LORE</mType>\
+                      <mConsumable>false</mConsumable>\
+                      <mExcludedFromCount>false</mExcludedFromCount>\
+                      </THItemRecord>\n  ";
+        let with_items = |count: usize| {
+            let records: String = (0..count)
+                .map(|n| record.replace("{}", &n.to_string()))
+                .collect();
+
+            save_with(&[(
+                "<KeyPoint>GotAxiomDisruptor",
+                &format!("{}<KeyPoint>GotAxiomDisruptor", records),
+            )])
+        };
+
+        // The save already has 2 items. 37 of 94 items is under 40%, 38
+        // isn't.
+        let under = with_items(35);
+        let over = with_items(36);
+
+        assert_eq!(status("low_percent", &under), AchievementStatus::Locked);
+        assert_eq!(status("low_percent", &over), AchievementStatus::Failed);
+    }
+
+    #[test]
+    fn passwords() {
+        let save = save_with(&[]);
+        let entered = save_with(&[(
+            "<AreaSaveData>",
+            "<PasswordEntry><mPassword>JUSTIN BAILEY</mPassword>\
+             <mEnabled>true</mEnabled></PasswordEntry>\n  <AreaSaveData>",
+        )]);
+        let flag = save_with(&[(
+            "<mJustinBailey>false",
+            "<mJustinBailey>true",
+        )]);
+
+        assert_eq!(status("password", &save), AchievementStatus::Locked);
+        assert_eq!(status("password", &entered), AchievementStatus::Unlocked);
+        assert_eq!(status("justin_bailey", &save), AchievementStatus::Locked);
+        assert_eq!(
+            status("justin_bailey", &entered),
+            AchievementStatus::Unlocked,
+        );
+        assert_eq!(status("justin_bailey", &flag), AchievementStatus::Unlocked);
+    }
+
+    #[test]
+    fn pacifist_fails_on_any_evidence() {
+        let save = save_with(&[]);
+        let killed = save_with(&[(
+            "<KeyPoint>XedurDefeated</KeyPoint>",
+            "<KeyPoint>XedurDefeated</KeyPoint>\n  \
+             <KeyPoint>CloneDefeated</KeyPoint>",
+        )]);
+
+        assert_eq!(status("pacifist", &save), AchievementStatus::Locked);
+        assert_eq!(status("pacifist", &killed), AchievementStatus::Failed);
+    }
+
+    #[test]
+    fn athetos_is_unknown() {
+        assert_eq!(
+            status("athetos", &save_with(&[])),
+            AchievementStatus::Unknown,
+        );
+    }
+
+    fn check(ids: &[&str], xml: &str) -> usize {
+        let savedata = savedata(xml);
+        let required: Vec<&dyn Achievement> = ids
+            .iter()
+            .map(|id| find(id).unwrap())
+            .collect();
+
+        Achievements::new(&savedata).check(&required)
+    }
+
+    #[test]
+    fn check_counts_failed_conditions() {
+        let save = save_with(&[]);
+
+        assert_eq!(check(&[], &save), 0);
+        assert_eq!(check(&["low_percent", "xedur"], &save), 0);
+        assert_eq!(check(&["invincible", "xedur"], &save), 1);
+        assert_eq!(check(&["invincible", "telal", "athetos"], &save), 3);
+    }
+
+    #[test]
+    fn finds_achievements_by_id() {
+        assert_eq!(find("LOW_PERCENT").unwrap().name(), "Low %");
+        assert_eq!(find("gir_tab").unwrap().name(), "Gir-Tab");
+        assert!(find("invincable").is_err());
+        assert!(find("ACH_INVINCIBLE").is_err());
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let ids: HashSet<String> = REGISTRY
+            .iter()
+            .map(|achievement| achievement.id())
+            .collect();
+
+        assert_eq!(ids.len(), REGISTRY.len());
+    }
+
+    #[test]
+    fn boss_kills_need_the_game_to_record_them() {
+        let savedata = savedata(&save_with(&[]));
+        let evaluation = boss_kill("Xedur").evaluate(&savedata);
+
+        assert_eq!(evaluation.status, AchievementStatus::Unlocked);
+    }
+
+    #[test]
+    fn boss_kills_show_weaker_evidence() {
+        // Visiting Zi suggests that Telal is dead, but isn't proof.
+        let xml = save_with(&[(
+            "<AutoMap>",
+            "<AreaSaveData><mAreaName>Zi</mAreaName><mSeed>222</mSeed>\
+             <mScreenCount>1</mScreenCount><mX>0</mX><mY>0</mY>\
+             </AreaSaveData>\n  <AutoMap>",
+        )]);
+        let savedata = savedata(&xml);
+        let evaluation = boss_kill("Telal").evaluate(&savedata);
+
+        assert_eq!(evaluation.status, AchievementStatus::Locked);
+        assert!(evaluation.progress.contains("Possible"));
+    }
 }
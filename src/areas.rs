@@ -0,0 +1,298 @@
+// areas: Per area exploration and item collection progress.
+use crate::items;
+use crate::savedata::THSaveData;
+use anyhow::{
+    anyhow,
+    Error,
+    Result,
+};
+use std::collections::BTreeSet;
+use std::str;
+
+// How to order the area report.
+#[derive(Clone, Copy, Debug)]
+pub enum AreaSort {
+    // The order areas are usually visited in.
+    Game,
+
+    // Alphabetical.
+    Name,
+
+    // Least complete areas first.
+    Incomplete,
+}
+
+impl str::FromStr for AreaSort {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "game"       => Ok(Self::Game),
+            "name"       => Ok(Self::Name),
+            "incomplete" => Ok(Self::Incomplete),
+            _            => Err(anyhow!("Unknown sort order: {}", s)),
+        }
+    }
+}
+
+// Progress through a single area.
+struct AreaProgress<'a> {
+    name: &'a str,
+    seed: Option<i32>,
+    screens: i32,
+    total_screens: Option<i32>,
+    items: usize,
+    total_items: usize,
+}
+
+impl<'a> AreaProgress<'a> {
+    // Percentage of the area's screens explored. Unknown for areas without a
+    // map, since we don't know how many screens they have.
+    fn screen_percent(&self) -> Option<f32> {
+        self.total_screens.map(|total| percent(self.screens, total))
+    }
+
+    fn item_percent(&self) -> f32 {
+        percent(self.items as i32, self.total_items as i32)
+    }
+
+    // How complete the area is, taken as the lower of screen and item
+    // completion. Areas without a map count as having no screens explored.
+    fn completion(&self) -> f32 {
+        let screens = self.screen_percent().unwrap_or(0.0);
+
+        screens.min(self.item_percent())
+    }
+
+    fn complete(&self) -> bool {
+        let screens_done = self.total_screens
+            .is_some_and(|total| self.screens >= total);
+
+        screens_done && self.items >= self.total_items
+    }
+}
+
+fn percent(current: i32, total: i32) -> f32 {
+    if total > 0 {
+        current as f32 / total as f32 * 100.0
+    }
+    else {
+        100.0
+    }
+}
+
+pub struct Areas<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> Areas<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    // Every area that we know about, either from the catalogue or the save.
+    fn area_names(&self) -> BTreeSet<&'a str> {
+        let visited = self.savedata.area_save_data
+            .iter()
+            .map(|area| area.area_name.as_str());
+
+        let mapped = self.savedata.auto_maps
+            .iter()
+            .map(|map| map.area_name.as_str());
+
+        items::AREAS
+            .iter()
+            .copied()
+            .chain(visited)
+            .chain(mapped)
+            .collect()
+    }
+
+    fn progress(&self, name: &'a str) -> AreaProgress<'a> {
+        let area = self.savedata.area_save_data
+            .iter()
+            .find(|area| area.area_name == name);
+
+        let total_screens = self.savedata.auto_maps
+            .iter()
+            .find(|map| map.area_name == name)
+            .map(|map| map.screen_count);
+
+        let collected: BTreeSet<&str> = area
+            .and_then(|area| area.items.as_ref())
+            .map(|items| items.iter().map(String::as_str).collect())
+            .unwrap_or_default();
+
+        // Items available are those known to be in the area, plus anything
+        // else that was collected there.
+        let available: BTreeSet<&str> = items::CATALOGUE
+            .iter()
+            .filter(|item| item.area == name)
+            .map(|item| item.id)
+            .chain(collected.iter().copied())
+            .collect();
+
+        AreaProgress {
+            name,
+            seed: area.map(|area| area.seed),
+            screens: area.map_or(0, |area| area.screen_count),
+            total_screens,
+            items: collected.len(),
+            total_items: available.len(),
+        }
+    }
+
+    // Progress for each area, in the requested order.
+    fn sorted(
+        &self,
+        sort: AreaSort,
+        incomplete_only: bool,
+    ) -> Vec<AreaProgress<'a>> {
+        let mut areas: Vec<AreaProgress> = self.area_names()
+            .into_iter()
+            .map(|name| self.progress(name))
+            .filter(|area| !incomplete_only || !area.complete())
+            .collect();
+
+        match sort {
+            AreaSort::Game => {
+                areas.sort_by_key(|area| {
+                    (items::area_order(area.name), area.name)
+                });
+            },
+            AreaSort::Name => {
+                areas.sort_by_key(|area| area.name);
+            },
+            AreaSort::Incomplete => {
+                areas.sort_by(|a, b| {
+                    a.completion().total_cmp(&b.completion())
+                });
+            },
+        }
+
+        areas
+    }
+
+    pub fn report(&self, sort: AreaSort, incomplete_only: bool) {
+        let areas = self.sorted(sort, incomplete_only);
+
+        println!("Area Progress:");
+
+        if areas.is_empty() {
+            println!("  - All areas complete");
+            return;
+        }
+
+        for area in areas {
+            let screens = match area.screen_percent() {
+                Some(percent) => format!(
+                    "{}/{} screens ({:.2}%)",
+                    area.screens,
+                    area.total_screens.unwrap_or_default(),
+                    percent,
+                ),
+                None => format!("{}/? screens", area.screens),
+            };
+
+            let seed = area.seed
+                .map_or_else(|| "unknown".to_string(), |seed| seed.to_string());
+
+            println!(
+                "  - {}: {}, {}/{} items ({:.2}%), seed {}",
+                area.name,
+                screens,
+                area.items,
+                area.total_items,
+                area.item_percent(),
+                seed,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata;
+
+    const ERIBU_MAP: &str = "<mAreaName>Eribu</mAreaName><mWidthScreens>";
+
+    #[test]
+    fn screens_and_items_are_separate() {
+        let game = testdata::savedata(testdata::SAVE);
+        let areas = Areas::new(&game);
+        let eribu = areas.progress("Eribu");
+
+        assert_eq!(eribu.screen_percent(), Some(100.0));
+        assert!(eribu.item_percent() < 100.0);
+        assert_eq!(eribu.completion(), eribu.item_percent());
+        assert!(!eribu.complete());
+    }
+
+    #[test]
+    fn items_count_without_a_map() {
+        let xml = testdata::save_with(&[
+            (ERIBU_MAP, "<mAreaName>Absu</mAreaName><mWidthScreens>"),
+        ]);
+        let game = testdata::savedata(&xml);
+        let areas = Areas::new(&game);
+        let eribu = areas.progress("Eribu");
+
+        assert_eq!(eribu.total_screens, None);
+        assert_eq!(eribu.screen_percent(), None);
+        assert_eq!(eribu.items, 1);
+        assert!(eribu.item_percent() > 0.0);
+        assert_eq!(eribu.completion(), 0.0);
+    }
+
+    #[test]
+    fn unvisited_areas_have_no_progress() {
+        let game = testdata::savedata(testdata::SAVE);
+        let areas = Areas::new(&game);
+        let zi = areas.progress("Zi");
+
+        assert_eq!(zi.seed, None);
+        assert_eq!(zi.screens, 0);
+        assert_eq!(zi.items, 0);
+        assert_eq!(zi.completion(), 0.0);
+    }
+
+    #[test]
+    fn sorts_by_the_lower_completion() {
+        // Absu is half explored with no items, so sorts before Eribu, which
+        // is fully explored with one item collected.
+        let xml = testdata::save_with(&[(
+            "<AutoMap>",
+            "<AreaSaveData><mAreaName>Absu</mAreaName><mSeed>222</mSeed>\
+             <mScreenCount>10</mScreenCount><mX>0</mX><mY>0</mY>\
+             </AreaSaveData>\
+             <AutoMap><mAreaName>Absu</mAreaName><mWidthScreens>10\
+             </mWidthScreens><mHeightScreens>10</mHeightScreens>\
+             <mScreenCount>20</mScreenCount><mCSVData>0</mCSVData>\
+             <Reminder><X>0</X><Y>0</Y></Reminder>\
+             </AutoMap><AutoMap>",
+        )]);
+        let game = testdata::savedata(&xml);
+        let areas = Areas::new(&game);
+        let sorted: Vec<&str> = areas.sorted(AreaSort::Incomplete, false)
+            .iter()
+            .map(|area| area.name)
+            .filter(|&name| name == "Absu" || name == "Eribu")
+            .collect();
+
+        assert_eq!(sorted, ["Absu", "Eribu"]);
+    }
+
+    #[test]
+    fn hides_complete_areas() {
+        let game = testdata::savedata(testdata::SAVE);
+        let areas = Areas::new(&game);
+        let all = areas.sorted(AreaSort::Name, false);
+        let incomplete = areas.sorted(AreaSort::Name, true);
+
+        assert!(incomplete.iter().all(|area| !area.complete()));
+        assert_eq!(all.len(), incomplete.len());
+    }
+}
@@ -0,0 +1,231 @@
+// bestiary: Bestiary completion, covering every creature in the game.
+use crate::savedata::{
+    Creature,
+    CreatureCategory,
+    THSaveData,
+};
+use anyhow::{
+    anyhow,
+    Error,
+    Result,
+};
+use std::collections::HashSet;
+use std::fmt;
+use std::str;
+
+// How to order the bestiary.
+#[derive(Clone, Copy, Debug)]
+pub enum BestiarySort {
+    // Alphabetical, by bestiary name.
+    Name,
+
+    // By bestiary category, then name.
+    Category,
+}
+
+impl str::FromStr for BestiarySort {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "name"     => Ok(Self::Name),
+            "category" => Ok(Self::Category),
+            _          => Err(anyhow!("Unknown sort order: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum CreatureStatus {
+    Glitched,
+    NotGlitched,
+    NotRequired,
+}
+
+impl fmt::Display for CreatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::Glitched    => "Glitched",
+            Self::NotGlitched => "Not glitched",
+            Self::NotRequired => "Not required",
+        };
+
+        write!(f, "{}", desc)
+    }
+}
+
+pub struct Bestiary<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> Bestiary<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    fn glitched(&self) -> HashSet<&'a Creature> {
+        self.savedata.creatures_glitched
+            .iter()
+            .flatten()
+            .collect()
+    }
+
+    // Every known creature, along with any unknown ones found in the save.
+    fn creatures(&self) -> Vec<Creature> {
+        let unknown = self.savedata.creatures_glitched
+            .iter()
+            .flatten()
+            .filter(|creature| matches!(creature, Creature::Unknown(_)));
+
+        let mut creatures: Vec<Creature> = Creature::ALL
+            .iter()
+            .chain(unknown)
+            .cloned()
+            .collect();
+
+        // Unknown creatures could be glitched more than once.
+        let mut seen = HashSet::new();
+        creatures.retain(|creature| seen.insert(creature.clone()));
+
+        creatures
+    }
+
+    fn status(
+        creature: &Creature,
+        glitched: &HashSet<&Creature>,
+    ) -> CreatureStatus {
+        if glitched.contains(creature) {
+            CreatureStatus::Glitched
+        }
+        else if creature.hacker() {
+            CreatureStatus::NotGlitched
+        }
+        else {
+            CreatureStatus::NotRequired
+        }
+    }
+
+    // Glitched and total counts for the creatures Hacker needs.
+    fn completion(&self) -> (usize, usize) {
+        let glitched = self.glitched();
+        let required = Creature::achievement_list();
+        let done = required
+            .iter()
+            .filter(|creature| glitched.contains(creature))
+            .count();
+
+        (done, required.len())
+    }
+
+    pub fn report(
+        &self,
+        sort: BestiarySort,
+        category: Option<CreatureCategory>,
+        remaining_only: bool,
+    ) {
+        let glitched = self.glitched();
+        let mut creatures: Vec<(Creature, CreatureStatus)> = self.creatures()
+            .into_iter()
+            .map(|creature| {
+                let status = Self::status(&creature, &glitched);
+                (creature, status)
+            })
+            .filter(|(creature, status)| {
+                category.is_none_or(|category| creature.category() == category)
+                    && (!remaining_only
+                        || *status == CreatureStatus::NotGlitched)
+            })
+            .collect();
+
+        match sort {
+            BestiarySort::Name => {
+                creatures.sort_by_key(|(creature, _)| {
+                    creature.name().to_string()
+                });
+            },
+            BestiarySort::Category => {
+                creatures.sort_by_key(|(creature, _)| {
+                    (creature.category(), creature.name().to_string())
+                });
+            },
+        }
+
+        let (done, required) = self.completion();
+
+        println!(
+            "Bestiary: {}/{} required creatures glitched",
+            done,
+            required,
+        );
+
+        if creatures.is_empty() {
+            println!("  - No creatures to show");
+            return;
+        }
+
+        for (creature, status) in creatures {
+            println!(
+                "  - {} ({}), {}: {}",
+                creature,
+                creature.id(),
+                creature.category(),
+                status,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata;
+
+    const GLITCHED: &str = "<CreatureGlitched>TrapClaw_Meta</CreatureGlitched>";
+
+    #[test]
+    fn counts_required_creatures() {
+        let game = testdata::savedata(testdata::SAVE);
+        let bestiary = Bestiary::new(&game);
+        let required = Creature::achievement_list().len();
+
+        assert_eq!(bestiary.completion(), (1, required));
+    }
+
+    #[test]
+    fn counts_each_creature_once() {
+        let xml = testdata::save_with(&[
+            (GLITCHED, &GLITCHED.repeat(2)),
+        ]);
+        let game = testdata::savedata(&xml);
+        let bestiary = Bestiary::new(&game);
+
+        assert_eq!(bestiary.completion().0, 1);
+    }
+
+    #[test]
+    fn doesnt_count_unrequired_creatures() {
+        let xml = testdata::save_with(&[(
+            GLITCHED,
+            "<CreatureGlitched>Jormite</CreatureGlitched>\
+             <CreatureGlitched>Mystery_Meta</CreatureGlitched>",
+        )]);
+        let game = testdata::savedata(&xml);
+        let bestiary = Bestiary::new(&game);
+        let creatures = bestiary.creatures();
+        let glitched = bestiary.glitched();
+        let unknown = Creature::Unknown("Mystery_Meta".to_string());
+
+        assert_eq!(bestiary.completion().0, 0);
+        assert_eq!(creatures.len(), Creature::ALL.len() + 1);
+        assert_eq!(
+            Bestiary::status(&unknown, &glitched),
+            CreatureStatus::Glitched,
+        );
+        assert_eq!(
+            Bestiary::status(&Creature::TrapClaw, &glitched),
+            CreatureStatus::NotGlitched,
+        );
+    }
+}
@@ -0,0 +1,286 @@
+// bosses: Work out which bosses have been defeated, from all the evidence in
+// the save data.
+//
+// Speedrun checkpoints record boss kills, but older saves don't have them. Key
+// points, later story milestones and the areas visited are also checked, each
+// giving less certainty than the last.
+use crate::keypoints::MILESTONES;
+use crate::savedata::THSaveData;
+use anyhow::{
+    anyhow,
+    Result,
+};
+use std::fmt;
+
+#[derive(Debug)]
+struct Boss {
+    // Name as it appears in the speedrun checkpoints.
+    name: &'static str,
+
+    // Key points recorded when the boss is defeated.
+    key_points: &'static [&'static str],
+
+    // Area that can't be reached until the boss is defeated.
+    unlocks: Option<&'static str>,
+
+    // Optional bosses don't block the story, so later progress says nothing
+    // about them.
+    optional: bool,
+}
+
+// Bosses in the order they're usually defeated.
+const BOSSES: &[Boss] = &[
+    Boss {
+        name: "Xedur",
+        key_points: &["XedurDefeated"],
+        unlocks: Some("Absu"),
+        optional: false,
+    },
+    Boss {
+        name: "Telal",
+        key_points: &["TelalDefeated"],
+        unlocks: Some("Zi"),
+        optional: false,
+    },
+    Boss {
+        name: "Uruku",
+        key_points: &["UrukuDefeated"],
+        unlocks: Some("Kur"),
+        optional: false,
+    },
+    Boss {
+        name: "Gir-Tab",
+        key_points: &["GirTabDefeated", "Gir-TabDefeated"],
+        unlocks: None,
+        optional: false,
+    },
+    Boss {
+        name: "Vision",
+        key_points: &["VisionDefeated"],
+        unlocks: None,
+        optional: false,
+    },
+    Boss {
+        name: "Clone",
+        key_points: &["CloneDefeated"],
+        unlocks: None,
+        optional: true,
+    },
+    Boss {
+        name: "Ukhu",
+        key_points: &["UkhuDefeated"],
+        unlocks: Some("E-Kur-Mah"),
+        optional: false,
+    },
+    Boss {
+        name: "Sentinel",
+        key_points: &["SentinelDefeated"],
+        unlocks: None,
+        optional: false,
+    },
+];
+
+// How sure we are that a boss has been defeated.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Confidence {
+    // Visited an area that's normally only reachable after the boss.
+    Possible,
+
+    // Completed a later part of the story. Sequence breaks can get past some
+    // bosses, so this isn't certain.
+    Likely,
+
+    // Recorded by the game itself.
+    Certain,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::Possible => "Possible",
+            Self::Likely   => "Likely",
+            Self::Certain  => "Certain",
+        };
+
+        write!(f, "{}", desc)
+    }
+}
+
+// The evidence that decided a boss was defeated.
+#[derive(Clone, Debug)]
+pub enum Signal {
+    Checkpoint,
+    KeyPoint(&'static str),
+    LaterMilestone(&'static str),
+    LaterArea(&'static str),
+}
+
+impl Signal {
+    fn confidence(&self) -> Confidence {
+        match self {
+            Self::Checkpoint | Self::KeyPoint(_) => Confidence::Certain,
+            Self::LaterMilestone(_)              => Confidence::Likely,
+            Self::LaterArea(_)                   => Confidence::Possible,
+        }
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Checkpoint => write!(f, "speedrun checkpoint"),
+            Self::KeyPoint(key_point) => write!(f, "key point {}", key_point),
+            Self::LaterMilestone(name) => {
+                write!(f, "later milestone: {}", name)
+            },
+            Self::LaterArea(area) => write!(f, "visited {}", area),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BossState {
+    Alive,
+    Defeated(Signal),
+}
+
+impl BossState {
+    pub fn is_defeated(&self) -> bool {
+        matches!(self, Self::Defeated(_))
+    }
+
+    // Whether the game itself recorded the boss as defeated.
+    pub fn is_certain(&self) -> bool {
+        match self {
+            Self::Defeated(signal) => {
+                signal.confidence() == Confidence::Certain
+            },
+            Self::Alive => false,
+        }
+    }
+}
+
+impl fmt::Display for BossState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Alive => write!(f, "Alive (no evidence of a kill)"),
+            Self::Defeated(signal) => {
+                write!(f, "Dead ({}, {})", signal.confidence(), signal)
+            },
+        }
+    }
+}
+
+pub struct Bosses<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> Bosses<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    fn checkpoint(&self, boss: &Boss) -> Option<Signal> {
+        self.savedata.speedrun_checkpoints
+            .iter()
+            .flatten()
+            .find(|checkpoint| checkpoint.name == boss.name)
+            .map(|_| Signal::Checkpoint)
+    }
+
+    fn key_point(&self, boss: &Boss) -> Option<Signal> {
+        boss.key_points
+            .iter()
+            .find(|key_point| {
+                self.savedata.key_points_completed
+                    .iter()
+                    .any(|completed| completed == *key_point)
+            })
+            .map(|key_point| Signal::KeyPoint(key_point))
+    }
+
+    // The first story milestone after the boss that's been completed.
+    fn later_milestone(&self, boss: &Boss) -> Option<Signal> {
+        if boss.optional {
+            return None;
+        }
+
+        let index = MILESTONES
+            .iter()
+            .position(|milestone| {
+                boss.key_points
+                    .iter()
+                    .any(|key_point| milestone.is_key_point(key_point))
+            })?;
+
+        MILESTONES[index + 1..]
+            .iter()
+            .find(|milestone| milestone.is_done(self.savedata))
+            .map(|milestone| Signal::LaterMilestone(milestone.name))
+    }
+
+    fn later_area(&self, boss: &Boss) -> Option<Signal> {
+        let area = boss.unlocks?;
+
+        let visited = self.savedata.area_save_data
+            .iter()
+            .any(|data| data.area_name == area);
+
+        let mapped = self.savedata.auto_maps
+            .iter()
+            .any(|map| map.area_name == area);
+
+        if visited || mapped {
+            Some(Signal::LaterArea(area))
+        }
+        else {
+            None
+        }
+    }
+
+    fn boss_state(&self, boss: &Boss) -> BossState {
+        self.checkpoint(boss)
+            .or_else(|| self.key_point(boss))
+            .or_else(|| self.later_milestone(boss))
+            .or_else(|| self.later_area(boss))
+            .map_or(BossState::Alive, BossState::Defeated)
+    }
+
+    // Checks that a boss name is one that we know, by the name used in the
+    // speedrun checkpoints.
+    pub fn validate_name(name: &str) -> Result<()> {
+        if BOSSES.iter().any(|boss| boss.name == name) {
+            return Ok(());
+        }
+
+        let names: Vec<&str> = BOSSES
+            .iter()
+            .map(|boss| boss.name)
+            .collect();
+
+        Err(anyhow!(
+            "Unknown boss: {}, expected one of: {}",
+            name,
+            names.join(", "),
+        ))
+    }
+
+    // State of a boss, by the name used in the speedrun checkpoints.
+    pub fn state(&self, name: &str) -> BossState {
+        BOSSES
+            .iter()
+            .find(|boss| boss.name == name)
+            .map_or(BossState::Alive, |boss| self.boss_state(boss))
+    }
+
+    // Prints the state of each boss, and the evidence for it.
+    pub fn report(&self) {
+        println!("Bosses:");
+
+        for boss in BOSSES {
+            println!("  - {}: {}", boss.name, self.boss_state(boss));
+        }
+    }
+}
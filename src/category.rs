@@ -0,0 +1,435 @@
+// category: Validate a save game against speedrun category rules.
+//
+// Categories are defined in a TOML file, every rule is optional:
+//
+//   [categories."any%-hard"]
+//   difficulty = "hard"
+//   cheats = false
+//   passwords = false
+//   randomizer = false
+//   speedrun_mode = true
+//   bosses = ["Xedur", "Telal"]
+//   achievements = ["mostly_invincible"]
+//   time_limit = "4h"
+//   clock = "game"
+use crate::achievements::{
+    self,
+    Achievement,
+};
+use crate::bosses::Bosses;
+use crate::clock::{
+    frames_to_duration,
+    Clock,
+};
+use crate::savedata::{
+    THDifficultySetting,
+    THSaveData,
+};
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::str;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Difficulty {
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    fn matches(self, setting: &THDifficultySetting) -> bool {
+        matches!(
+            (self, setting),
+            (Self::Normal, THDifficultySetting::Normal)
+                | (Self::Hard, THDifficultySetting::Hard)
+        )
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::Normal => "normal",
+            Self::Hard   => "hard",
+        };
+
+        write!(f, "{}", desc)
+    }
+}
+
+// Rules for a single category. Unknown rules are rejected, so that a typo
+// can't make a rule silently pass.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CategoryRules {
+    // Game difficulty, normal or hard.
+    difficulty: Option<Difficulty>,
+
+    // Whether cheats are allowed.
+    cheats: Option<bool>,
+
+    // Whether passwords, including JUSTIN BAILEY, are allowed.
+    passwords: Option<bool>,
+
+    // Whether the save must, or must not, be a randomizer seed.
+    randomizer: Option<bool>,
+
+    // Whether the save must, or must not, be in speedrun mode.
+    speedrun_mode: Option<bool>,
+
+    // Bosses that must have been defeated, by speedrun checkpoint name. Only
+    // kills recorded by the game count.
+    #[serde(default)]
+    bosses: Vec<String>,
+
+    // Achievement conditions that must hold, by ID.
+    #[serde(default)]
+    achievements: Vec<String>,
+
+    // Time the run must be finished within, eg. "4h" or "1h 30m".
+    time_limit: Option<String>,
+
+    // Clock that the time limit uses, real or game. Defaults to game.
+    clock: Option<Clock>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CategoriesFile {
+    #[serde(default)]
+    categories: BTreeMap<String, CategoryRules>,
+}
+
+// Outcome of checking a single rule.
+struct RuleResult {
+    rule: String,
+    passed: bool,
+    detail: String,
+}
+
+impl RuleResult {
+    fn new(rule: String, passed: bool, detail: String) -> Self {
+        Self {
+            rule,
+            passed,
+            detail,
+        }
+    }
+
+    // Rules that something is, or isn't, allowed.
+    fn allowed(name: &str, allowed: bool, used: bool, detail: String) -> Self {
+        let rule = if allowed {
+            format!("{} allowed", name)
+        }
+        else {
+            format!("No {}", name.to_lowercase())
+        };
+
+        Self::new(rule, allowed || !used, detail)
+    }
+
+    // Rules that a setting must be on or off.
+    fn setting(name: &str, wanted: bool, actual: bool) -> Self {
+        let rule = if wanted {
+            format!("{} on", name)
+        }
+        else {
+            format!("{} off", name)
+        };
+
+        let detail = if actual {
+            "On"
+        }
+        else {
+            "Off"
+        };
+
+        Self::new(rule, wanted == actual, detail.to_string())
+    }
+}
+
+pub struct Category {
+    name: String,
+    rules: CategoryRules,
+}
+
+impl Category {
+    // Loads the named category from a categories file.
+    pub fn load(filename: &str, name: &str) -> Result<Self> {
+        let data = fs::read_to_string(filename)
+            .with_context(|| format!("Couldn't read categories {}", filename))?;
+        let mut file: CategoriesFile = toml::from_str(&data).with_context(|| {
+            format!("Couldn't parse categories {}", filename)
+        })?;
+
+        let rules = file.categories
+            .remove(name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = file.categories
+                    .keys()
+                    .map(String::as_str)
+                    .collect();
+
+                anyhow!(
+                    "Unknown category: {}, {} has: {}",
+                    name,
+                    filename,
+                    names.join(", "),
+                )
+            })?;
+
+        Ok(Self {
+            name: name.to_string(),
+            rules,
+        })
+    }
+
+    fn time_limit(&self) -> Result<Option<(Duration, Clock)>> {
+        let limit = match &self.rules.time_limit {
+            Some(limit) => limit,
+            None        => return Ok(None),
+        };
+
+        let limit = humantime::parse_duration(limit)
+            .with_context(|| format!("Invalid time limit: {}", limit))?;
+
+        let clock = self.rules.clock.unwrap_or(Clock::Game);
+
+        Ok(Some((limit, clock)))
+    }
+
+    fn check(&self, savedata: &THSaveData) -> Result<Vec<RuleResult>> {
+        let rules = &self.rules;
+        let mut results = Vec::new();
+
+        // Resolve everything up front, so that mistakes in the rules are
+        // errors rather than failures.
+        let achievements = rules.achievements
+            .iter()
+            .map(|id| achievements::find(id))
+            .collect::<Result<Vec<&dyn Achievement>>>()?;
+
+        for boss in &rules.bosses {
+            Bosses::validate_name(boss)?;
+        }
+
+        let time_limit = self.time_limit()?;
+
+        if let Some(difficulty) = rules.difficulty {
+            results.push(RuleResult::new(
+                format!("Difficulty is {}", difficulty),
+                difficulty.matches(&savedata.difficulty),
+                savedata.difficulty.to_string(),
+            ));
+        }
+
+        if let Some(allowed) = rules.cheats {
+            let used = savedata.cheats_used;
+            let detail = if used {
+                "Cheats used"
+            }
+            else {
+                "No cheats used"
+            };
+
+            results.push(RuleResult::allowed(
+                "Cheats",
+                allowed,
+                used,
+                detail.to_string(),
+            ));
+        }
+
+        if let Some(allowed) = rules.passwords {
+            let entered = savedata.passwords.len();
+            let used = entered > 0 || savedata.justin_bailey;
+            let detail = format!("{} entered", entered);
+
+            results.push(RuleResult::allowed(
+                "Passwords",
+                allowed,
+                used,
+                detail,
+            ));
+        }
+
+        if let Some(wanted) = rules.randomizer {
+            let actual = savedata.is_randomizer.unwrap_or(false);
+
+            results.push(RuleResult::setting("Randomizer", wanted, actual));
+        }
+
+        if let Some(wanted) = rules.speedrun_mode {
+            let actual = savedata.is_speed_run;
+
+            results.push(RuleResult::setting("Speedrun mode", wanted, actual));
+        }
+
+        let bosses = Bosses::new(savedata);
+
+        for boss in &rules.bosses {
+            let state = bosses.state(boss);
+
+            results.push(RuleResult::new(
+                format!("Defeated {}", boss),
+                state.is_certain(),
+                state.to_string(),
+            ));
+        }
+
+        for achievement in achievements {
+            let evaluation = achievement.evaluate(savedata);
+
+            results.push(RuleResult::new(
+                format!("{} holds", achievement.name()),
+                evaluation.holds(),
+                format!("{} ({})", evaluation.progress, evaluation.status),
+            ));
+        }
+
+        if let Some((limit, clock)) = time_limit {
+            let time = frames_to_duration(clock.frames(savedata));
+
+            results.push(RuleResult::new(
+                format!(
+                    "Under {} {}",
+                    humantime::format_duration(limit),
+                    clock,
+                ),
+                *time.get_ref() < limit,
+                time.to_string(),
+            ));
+        }
+
+        Ok(results)
+    }
+
+    // Prints the result of every rule in the category. Returns the number of
+    // rules that failed.
+    pub fn validate(&self, savedata: &THSaveData) -> Result<usize> {
+        let results = self.check(savedata)?;
+
+        println!("Category {}:", self.name);
+
+        for result in &results {
+            let outcome = if result.passed {
+                "Passed"
+            }
+            else {
+                "Failed"
+            };
+
+            println!("  - {}: {} ({})", result.rule, outcome, result.detail);
+        }
+
+        let failed = results
+            .iter()
+            .filter(|result| !result.passed)
+            .count();
+
+        Ok(failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{
+        save_with,
+        savedata,
+    };
+
+    fn parse(toml: &str) -> Result<CategoriesFile> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    fn category(toml: &str) -> Category {
+        let mut file = parse(toml).unwrap();
+
+        Category {
+            name: "test".to_string(),
+            rules: file.categories.remove("test").unwrap(),
+        }
+    }
+
+    fn passed(category: &Category, xml: &str) -> Vec<bool> {
+        category.check(&savedata(xml))
+            .unwrap()
+            .iter()
+            .map(|result| result.passed)
+            .collect()
+    }
+
+    #[test]
+    fn rejects_unknown_rules() {
+        assert!(parse("[categories.test]\ndifficulty = \"hard\"").is_ok());
+        assert!(parse("[categories.test]\ndificulty = \"hard\"").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_difficulties() {
+        assert!(parse("[categories.test]\ndifficulty = \"Hard\"").is_err());
+        assert!(parse("[categories.test]\ndifficulty = \"hrad\"").is_err());
+    }
+
+    #[test]
+    fn checks_difficulty() {
+        let category = category("[categories.test]\ndifficulty = \"hard\"");
+        let hard = save_with(&[("NORMAL", "HARD")]);
+
+        assert_eq!(passed(&category, &save_with(&[])), [false]);
+        assert_eq!(passed(&category, &hard), [true]);
+    }
+
+    #[test]
+    fn bosses_need_a_recorded_kill() {
+        // Visiting Zi suggests that Telal is dead, but isn't proof.
+        let category = category(
+            "[categories.test]\nbosses = [\"Xedur\", \"Telal\"]",
+        );
+        let xml = save_with(&[(
+            "<AutoMap>",
+            "<AreaSaveData><mAreaName>Zi</mAreaName><mSeed>222</mSeed>\
+             <mScreenCount>1</mScreenCount><mX>0</mX><mY>0</mY>\
+             </AreaSaveData>\n  <AutoMap>",
+        )]);
+
+        assert_eq!(passed(&category, &xml), [true, false]);
+    }
+
+    #[test]
+    fn rejects_unknown_bosses() {
+        let category = category("[categories.test]\nbosses = [\"Xedr\"]");
+
+        assert!(category.check(&savedata(&save_with(&[]))).is_err());
+    }
+
+    #[test]
+    fn checks_time_limit() {
+        // The test save has 400000 frames of game time, about 1h 51m.
+        let category = category(
+            "[categories.test]\ntime_limit = \"2h\"\nclock = \"game\"",
+        );
+        let slow = save_with(&[(
+            "<mEffectiveFrames>400000",
+            "<mEffectiveFrames>500000",
+        )]);
+
+        assert_eq!(passed(&category, &save_with(&[])), [true]);
+        assert_eq!(passed(&category, &slow), [false]);
+    }
+
+    #[test]
+    fn rejects_unknown_clocks() {
+        assert!(parse("[categories.test]\nclock = \"real\"").is_ok());
+        assert!(parse("[categories.test]\nclock = \"Game\"").is_err());
+        assert!(parse("[categories.test]\nclock = \"wall\"").is_err());
+    }
+}
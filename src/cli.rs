@@ -4,6 +4,7 @@ use clap::{
     crate_name,
     crate_version,
     App,
+    AppSettings,
     Arg,
     ArgMatches,
     SubCommand,
@@ -13,6 +14,127 @@ use clap::{
 fn create_app<'a, 'b>() -> App<'a, 'b> {
     let achievements = SubCommand::with_name("achievements")
         .about("Display all Achievement progress")
+        .arg(
+            Arg::with_name("VERBOSE")
+                .help("Show the description and Steam name of achievements")
+                .long("verbose")
+                .short("v")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("STEAM")
+                .help("Compare with Steam's achievements, read from the \
+                       Steam install or its appcache/stats directory")
+                .long("steam")
+                .value_name("STEAM_DIR")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("STEAM_USER")
+                .help("Steam account ID to read achievements for, if \
+                       more than one user has played")
+                .long("steam-user")
+                .requires("STEAM")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to analyse")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let areas = SubCommand::with_name("areas")
+        .about("Display exploration and item progress for each area")
+        .arg(
+            Arg::with_name("INCOMPLETE")
+                .help("Only list areas that aren't complete")
+                .long("incomplete")
+                .short("i")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("SORT")
+                .help("Order to list areas in")
+                .long("sort")
+                .short("s")
+                .takes_value(true)
+                .possible_values(&["game", "name", "incomplete"])
+                .default_value("game")
+        )
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to analyse")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let bestiary = SubCommand::with_name("bestiary")
+        .about("Lists every creature in the bestiary and its glitch status")
+        .arg(
+            Arg::with_name("CATEGORY")
+                .help("Only list creatures in the given category")
+                .long("category")
+                .short("c")
+                .takes_value(true)
+                .case_insensitive(true)
+                .possible_values(&[
+                    "fauna",
+                    "flora",
+                    "mechanized",
+                    "other",
+                    "unknown",
+                ])
+        )
+        .arg(
+            Arg::with_name("REMAINING")
+                .help("Only list creatures still needed for Hacker")
+                .long("remaining")
+                .short("r")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("SORT")
+                .help("Order to list creatures in")
+                .long("sort")
+                .short("s")
+                .takes_value(true)
+                .possible_values(&["name", "category"])
+                .default_value("name")
+        )
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to analyse")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let bosses = SubCommand::with_name("bosses")
+        .about("Display which bosses have been defeated, and the evidence")
         .arg(
             Arg::with_name("UNENCRYPTED")
                 .help("Specify if operating on an unencrypted save game")
@@ -28,37 +150,285 @@ fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true)
         );
 
+    let category = SubCommand::with_name("category")
+        .about("Check a save game against speedrun category rules")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("validate")
+                .about("Checks every rule for the category")
+                .arg(
+                    Arg::with_name("CATEGORY")
+                        .help("Name of the category to check against")
+                        .long("category")
+                        .short("c")
+                        .takes_value(true)
+                        .required(true)
+                )
+                .arg(
+                    Arg::with_name("RULES")
+                        .help("TOML file containing the category rules")
+                        .long("rules")
+                        .takes_value(true)
+                        .default_value("categories.toml")
+                )
+                .arg(
+                    Arg::with_name("UNENCRYPTED")
+                        .help("Specify if operating on an unencrypted save game")
+                        .long("unencrypted")
+                        .short("u")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Save game to check")
+                        .index(1)
+                        .required(true)
+                        .takes_value(true)
+                )
+        );
+
+    let check = SubCommand::with_name("check")
+        .about("Check that achievement conditions hold, for scripts")
+        .arg(
+            Arg::with_name("REQUIRE")
+                .help("ID of an achievement whose condition must hold, as \
+                       shown by achievements --verbose")
+                .long("require")
+                .short("r")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .required(true)
+        )
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to check")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let creatures = SubCommand::with_name("creatures")
+        .about("Mark creatures as glitched or not glitched")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("glitch")
+                .about("Mark creatures as glitched")
+                .arg(
+                    Arg::with_name("FORCE")
+                        .help("Overwrite the output file, keeping a backup of it")
+                        .long("force")
+                        .short("f")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("IN_PLACE")
+                        .help("Replace the input file, keeping a backup of it")
+                        .long("in-place")
+                        .short("i")
+                        .takes_value(false)
+                        .conflicts_with("OUTPUT")
+                )
+                .arg(
+                    Arg::with_name("OUTPUT")
+                        .help("File to write the edited save game to")
+                        .long("output")
+                        .short("o")
+                        .takes_value(true)
+                )
+                .arg(
+                    Arg::with_name("UNENCRYPTED")
+                        .help("Specify if operating on an unencrypted save game")
+                        .long("unencrypted")
+                        .short("u")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Save game to edit")
+                        .index(1)
+                        .required(true)
+                        .takes_value(true)
+                )
+                .arg(
+                    Arg::with_name("CREATURE")
+                        .help("Creature ID or bestiary name to glitch")
+                        .index(2)
+                        .required(true)
+                        .multiple(true)
+                        .takes_value(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("unglitch")
+                .about("Mark creatures as not glitched")
+                .arg(
+                    Arg::with_name("FORCE")
+                        .help("Overwrite the output file, keeping a backup of it")
+                        .long("force")
+                        .short("f")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("IN_PLACE")
+                        .help("Replace the input file, keeping a backup of it")
+                        .long("in-place")
+                        .short("i")
+                        .takes_value(false)
+                        .conflicts_with("OUTPUT")
+                )
+                .arg(
+                    Arg::with_name("OUTPUT")
+                        .help("File to write the edited save game to")
+                        .long("output")
+                        .short("o")
+                        .takes_value(true)
+                )
+                .arg(
+                    Arg::with_name("UNENCRYPTED")
+                        .help("Specify if operating on an unencrypted save game")
+                        .long("unencrypted")
+                        .short("u")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Save game to edit")
+                        .index(1)
+                        .required(true)
+                        .takes_value(true)
+                )
+                .arg(
+                    Arg::with_name("CREATURE")
+                        .help("Creature ID or bestiary name to unglitch")
+                        .index(2)
+                        .required(true)
+                        .multiple(true)
+                        .takes_value(true)
+                )
+        );
+
     let decrypt = SubCommand::with_name("decrypt")
         .about("Decrypt an Axiom Verge Steam file")
+        .arg(
+            Arg::with_name("PRETTY")
+                .help("Indent the decrypted XML so that it's easy to read")
+                .long("pretty")
+                .short("p")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("SORT")
+                .help("Sort items, key points and creatures when formatting")
+                .long("sort")
+                .short("s")
+                .takes_value(false)
+                .requires("PRETTY")
+        )
+        .arg(
+            Arg::with_name("FORCE")
+                .help("Overwrite the output file, keeping a backup of it")
+                .long("force")
+                .short("f")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("IN_PLACE")
+                .help("Replace the input file, keeping a backup of it")
+                .long("in-place")
+                .short("i")
+                .takes_value(false)
+                .conflicts_with("OUTPUT")
+        )
         .arg(
             Arg::with_name("INPUT")
-                .help("File to decrypt")
+                .help("File to decrypt, or - for STDIN")
                 .index(1)
                 .required(true)
                 .takes_value(true)
         )
         .arg(
             Arg::with_name("OUTPUT")
-                .help("File to output to")
+                .help("File to output to, or - for STDOUT")
                 .index(2)
                 .takes_value(true)
         );
 
     let encrypt = SubCommand::with_name("encrypt")
         .about("Encrypt a file for Axiom Verge on Steam")
+        .arg(
+            Arg::with_name("FORCE")
+                .help("Overwrite the output file, keeping a backup of it")
+                .long("force")
+                .short("f")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("IN_PLACE")
+                .help("Replace the input file, keeping a backup of it")
+                .long("in-place")
+                .short("i")
+                .takes_value(false)
+                .conflicts_with("OUTPUT")
+        )
         .arg(
             Arg::with_name("INPUT")
-                .help("File to encrypt")
+                .help("File to encrypt, or - for STDIN")
                 .index(1)
                 .required(true)
                 .takes_value(true)
         )
         .arg(
             Arg::with_name("OUTPUT")
-                .help("File to output encrypted content to")
+                .help("File to output encrypted content to, or - for STDOUT")
                 .index(2)
+                .required_unless("IN_PLACE")
+                .takes_value(true)
+        );
+
+    let fmt = SubCommand::with_name("fmt")
+        .about("Indent decrypted save data, optionally in a canonical order")
+        .arg(
+            Arg::with_name("FORCE")
+                .help("Overwrite the output file, keeping a backup of it")
+                .long("force")
+                .short("f")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("IN_PLACE")
+                .help("Replace the input file, keeping a backup of it")
+                .long("in-place")
+                .short("i")
+                .takes_value(false)
+                .conflicts_with("OUTPUT")
+        )
+        .arg(
+            Arg::with_name("SORT")
+                .help("Sort items, key points and creatures")
+                .long("sort")
+                .short("s")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Decrypted save game to format, or - for STDIN")
+                .index(1)
                 .required(true)
                 .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("OUTPUT")
+                .help("File to output to, or - for STDOUT")
+                .index(2)
+                .takes_value(true)
         );
 
     let hacker = SubCommand::with_name("hacker")
@@ -78,15 +448,305 @@ fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true)
         );
 
+    let info = SubCommand::with_name("info")
+        .about("Display a summary of a save game, including play time")
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to analyse")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let lowpercent = SubCommand::with_name("lowpercent")
+        .about("Plan a Low % run")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("plan")
+                .about("Lists required items and the items left to spare")
+                .arg(
+                    Arg::with_name("UNENCRYPTED")
+                        .help("Specify if operating on an unencrypted save game")
+                        .long("unencrypted")
+                        .short("u")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Save game to analyse")
+                        .index(1)
+                        .required(true)
+                        .takes_value(true)
+                )
+        );
+
+    let open = SubCommand::with_name("open")
+        .about("Edit a save game in $EDITOR, replacing it if the edit is valid")
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to edit")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let pace = SubCommand::with_name("pace")
+        .about("Project the finishing time against a time limit")
+        .arg(
+            Arg::with_name("CLOCK")
+                .help("Time with real time or game time, defaults to the \
+                       in-game timer setting")
+                .long("clock")
+                .short("c")
+                .takes_value(true)
+                .possible_values(&["real", "game"])
+        )
+        .arg(
+            Arg::with_name("TARGET")
+                .help("Achievement time limit to plan against")
+                .long("target")
+                .short("t")
+                .takes_value(true)
+                .possible_values(&["overclocked", "speedrunner"])
+                .default_value("overclocked")
+        )
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to analyse")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let passwords = SubCommand::with_name("passwords")
+        .about("Lists entered passwords, optionally adding or removing them")
+        .arg(
+            Arg::with_name("FORCE")
+                .help("Overwrite the output file, keeping a backup of it")
+                .long("force")
+                .short("f")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("IN_PLACE")
+                .help("Replace the input file, keeping a backup of it")
+                .long("in-place")
+                .short("i")
+                .takes_value(false)
+                .conflicts_with("OUTPUT")
+        )
+        .arg(
+            Arg::with_name("OUTPUT")
+                .help("File to write the edited save game to")
+                .long("output")
+                .short("o")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("ADD")
+                .help("Password to add to the save game")
+                .long("add")
+                .short("a")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+        )
+        .arg(
+            Arg::with_name("REMOVE")
+                .help("Password to remove from the save game")
+                .long("remove")
+                .short("r")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+        )
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to analyse")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let progress = SubCommand::with_name("progress")
+        .about("Display main story progress")
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to analyse")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let randomizer = SubCommand::with_name("randomizer")
+        .about("Inspect randomizer seeds")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("spoiler")
+                .about("Lists every randomized item location, by area")
+                .arg(
+                    Arg::with_name("REMAINING")
+                        .help("Only list items that haven't been collected")
+                        .long("remaining")
+                        .short("r")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("UNENCRYPTED")
+                        .help("Specify if operating on an unencrypted save game")
+                        .long("unencrypted")
+                        .short("u")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Save game to analyse")
+                        .index(1)
+                        .required(true)
+                        .takes_value(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("track")
+                .about("Lists collected item locations without spoilers")
+                .arg(
+                    Arg::with_name("UNENCRYPTED")
+                        .help("Specify if operating on an unencrypted save game")
+                        .long("unencrypted")
+                        .short("u")
+                        .takes_value(false)
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Save game to analyse")
+                        .index(1)
+                        .required(true)
+                        .takes_value(true)
+                )
+        );
+
+    let secretworlds = SubCommand::with_name("secretworlds")
+        .about("Lists visited secret worlds and the items within them")
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to analyse")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
+    let verify = SubCommand::with_name("verify")
+        .about("Check a save game for problems before loading it in game")
+        .arg(
+            Arg::with_name("UNENCRYPTED")
+                .help("Specify if operating on an unencrypted save game")
+                .long("unencrypted")
+                .short("u")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Save game to verify")
+                .index(1)
+                .required(true)
+                .takes_value(true)
+        );
+
     App::new(crate_name!())
         .version(crate_version!())
         .about(crate_description!())
+        .arg(
+            Arg::with_name("PROFILE")
+                .help("Key profile to use, or auto to try every profile")
+                .long("profile")
+                .takes_value(true)
+                .default_value("steam")
+                .global(true)
+        )
+        .arg(
+            Arg::with_name("KEY_FILE")
+                .help("TOML file containing the key and iv to use")
+                .long("key-file")
+                .takes_value(true)
+                .global(true)
+        )
+        .arg(
+            Arg::with_name("PROFILES")
+                .help("TOML file containing additional key profiles")
+                .long("profiles")
+                .takes_value(true)
+                .global(true)
+        )
         .subcommand(achievements)
+        .subcommand(areas)
+        .subcommand(bestiary)
+        .subcommand(bosses)
+        .subcommand(category)
+        .subcommand(check)
+        .subcommand(creatures)
         .subcommand(decrypt)
         .subcommand(encrypt)
+        .subcommand(fmt)
         .subcommand(hacker)
+        .subcommand(info)
+        .subcommand(lowpercent)
+        .subcommand(open)
+        .subcommand(pace)
+        .subcommand(passwords)
+        .subcommand(progress)
+        .subcommand(randomizer)
+        .subcommand(secretworlds)
+        .subcommand(verify)
 }
 
 pub fn parse_args<'a>() -> ArgMatches<'a> {
     create_app().get_matches()
 }
+
+// Parses the given arguments, starting with the program name.
+#[cfg(test)]
+pub fn parse_args_from<'a>(args: &[&str]) -> ArgMatches<'a> {
+    create_app().get_matches_from(args)
+}
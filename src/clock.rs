@@ -0,0 +1,92 @@
+// clock: Real time and game time, as recorded in the save data.
+//
+// The save data keeps two clocks. Real time (mTotalFrames) counts every frame
+// since the save game was started, including loading and pauses. Game time
+// (mEffectiveFrames) only counts frames where the game was running. The
+// in-game timer shows game time, unless mUseRealTimers is set.
+use crate::savedata::THSaveData;
+use anyhow::{
+    anyhow,
+    Error,
+    Result,
+};
+use humantime::{
+    format_duration,
+    FormattedDuration,
+};
+use serde::Deserialize;
+use std::fmt;
+use std::str;
+use std::time::Duration;
+
+// Axiom verge time is based on 60fps with a 16.67ms frame time.
+const FRAMETIME_MS: f64 = 0.01667;
+
+// Turns frame counts into real time duration.
+pub fn frames_to_duration(frames: f64) -> FormattedDuration {
+    let seconds = (frames * FRAMETIME_MS) as u64;
+    let duration = Duration::from_secs(seconds);
+
+    format_duration(duration)
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Clock {
+    // Real time, for RTA.
+    Real,
+
+    // Game time, for IGT.
+    Game,
+}
+
+impl Clock {
+    // The clock the save's in-game timer uses.
+    pub fn for_savedata(savedata: &THSaveData) -> Self {
+        if savedata.use_real_timers {
+            Self::Real
+        }
+        else {
+            Self::Game
+        }
+    }
+
+    // Frames played so far, according to this clock.
+    pub fn frames(self, savedata: &THSaveData) -> f64 {
+        match self {
+            Self::Real => savedata.total_frames as f64,
+            Self::Game => savedata.effective_frames,
+        }
+    }
+
+    // The other clock, for showing both.
+    pub fn other(self) -> Self {
+        match self {
+            Self::Real => Self::Game,
+            Self::Game => Self::Real,
+        }
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::Real => "real time",
+            Self::Game => "game time",
+        };
+
+        write!(f, "{}", desc)
+    }
+}
+
+impl str::FromStr for Clock {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "real" => Ok(Self::Real),
+            "game" => Ok(Self::Game),
+            _      => Err(anyhow!("Unknown clock: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+// creatures: Mark creatures as glitched, or not, in the raw save data.
+use crate::document::{
+    Document,
+    Element,
+};
+use crate::savedata::Creature;
+use anyhow::{
+    anyhow,
+    Result,
+};
+use std::str;
+
+// How similar a name has to be to be suggested, from 0.0 to 1.0.
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+// Maximum number of suggestions to give for a creature we don't know.
+const MAX_SUGGESTIONS: usize = 3;
+
+// Normalise a creature name or ID for comparison, ignoring case, spaces and
+// punctuation. "Red Scissorbeak", "red-scissorbeak" and "RedScissorBeak" are
+// all the same.
+fn normalise(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// Creatures with names or IDs similar to the query, best match first.
+fn suggestions(query: &str) -> Vec<&'static Creature> {
+    let query = normalise(query);
+
+    let mut scored: Vec<(f64, &Creature)> = Creature::ALL
+        .iter()
+        .map(|creature| {
+            let score = [creature.id(), creature.name()]
+                .iter()
+                .map(|name| strsim::jaro_winkler(&query, &normalise(name)))
+                .fold(0.0, f64::max);
+
+            (score, creature)
+        })
+        .filter(|(score, _)| *score >= SUGGESTION_THRESHOLD)
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, creature)| creature)
+        .collect()
+}
+
+// Finds a creature by its ID (TrapClaw_Meta) or bestiary name
+// (Red Scissorbeak).
+pub fn resolve(query: &str) -> Result<Creature> {
+    // Exact IDs first, which includes any other IDs a creature is known by.
+    let creature = Creature::from_id(query);
+    if !matches!(creature, Creature::Unknown(_)) {
+        return Ok(creature);
+    }
+
+    let normalised = normalise(query);
+    let creature = Creature::ALL
+        .iter()
+        .find(|creature| {
+            normalise(creature.id()) == normalised
+                || normalise(creature.name()) == normalised
+        });
+
+    if let Some(creature) = creature {
+        return Ok(creature.clone());
+    }
+
+    let suggestions: Vec<String> = suggestions(query)
+        .into_iter()
+        .map(|creature| format!("{} ({})", creature, creature.id()))
+        .collect();
+
+    if suggestions.is_empty() {
+        Err(anyhow!("Unknown creature: {}", query))
+    }
+    else {
+        Err(anyhow!(
+            "Unknown creature: {}, did you mean: {}?",
+            query,
+            suggestions.join(", "),
+        ))
+    }
+}
+
+fn is_glitched(element: &Element, creature: &Creature) -> bool {
+    Creature::from_id(element.text().trim()) == *creature
+}
+
+// Adds a creature to the glitched creatures in the raw save data.
+pub fn glitch(document: &mut Document, creature: &Creature) -> Result<()> {
+    let glitched = document.root
+        .elements_named("CreatureGlitched")
+        .any(|element| is_glitched(element, creature));
+
+    if glitched {
+        return Err(anyhow!("{} has already been glitched", creature));
+    }
+
+    let element = Element::with_text("CreatureGlitched", creature.id());
+
+    // Glitched creatures are the last thing in the save data.
+    document.root.insert_element(element, &[]);
+
+    Ok(())
+}
+
+// Removes a creature from the glitched creatures in the raw save data.
+pub fn unglitch(document: &mut Document, creature: &Creature) -> Result<()> {
+    let removed = document.root.remove_elements("CreatureGlitched", |element| {
+        is_glitched(element, creature)
+    });
+
+    if removed == 0 {
+        return Err(anyhow!("{} hasn't been glitched", creature));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{
+        save_with,
+        SAVE,
+    };
+
+    fn parse(xml: &str) -> Document {
+        Document::parse(xml.as_bytes()).unwrap()
+    }
+
+    fn glitched(document: &Document) -> Vec<String> {
+        document.root
+            .elements_named("CreatureGlitched")
+            .map(|element| element.text())
+            .collect()
+    }
+
+    #[test]
+    fn resolves_ids_and_names() {
+        assert_eq!(resolve("TrapClaw_Meta").unwrap(), Creature::TrapClawMeta);
+        assert_eq!(resolve("TubeWorm_Meta").unwrap(), Creature::TubePuff);
+        assert_eq!(resolve("Red Scissorbeak").unwrap(), Creature::TrapClawMeta);
+        assert_eq!(resolve("red-scissorbeak").unwrap(), Creature::TrapClawMeta);
+        assert_eq!(resolve("trapclawmeta").unwrap(), Creature::TrapClawMeta);
+    }
+
+    #[test]
+    fn suggests_similar_names() {
+        let error = resolve("Red Scisorbeak").unwrap_err().to_string();
+
+        assert!(error.starts_with("Unknown creature: Red Scisorbeak, did you \
+                                   mean: Red Scissorbeak (TrapClaw_Meta)"));
+    }
+
+    #[test]
+    fn rejects_unknown_names_without_suggestions() {
+        let error = resolve("Xyzzy").unwrap_err().to_string();
+
+        assert_eq!(error, "Unknown creature: Xyzzy");
+    }
+
+    #[test]
+    fn glitches_creatures() {
+        let mut document = parse(SAVE);
+
+        glitch(&mut document, &Creature::Hookfish).unwrap();
+
+        assert_eq!(glitched(&document), ["TrapClaw_Meta", "Hookfish"]);
+    }
+
+    #[test]
+    fn rejects_glitching_twice() {
+        let mut document = parse(SAVE);
+        let error = glitch(&mut document, &Creature::TrapClawMeta)
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(error, "Red Scissorbeak has already been glitched");
+        assert_eq!(glitched(&document), ["TrapClaw_Meta"]);
+    }
+
+    #[test]
+    fn rejects_glitching_twice_by_another_id() {
+        let xml = save_with(&[("TrapClaw_Meta", "TubeWorm_Meta")]);
+        let mut document = parse(&xml);
+
+        assert!(glitch(&mut document, &Creature::TubePuff).is_err());
+    }
+
+    #[test]
+    fn unglitches_creatures() {
+        let mut document = parse(SAVE);
+
+        unglitch(&mut document, &Creature::TrapClawMeta).unwrap();
+
+        assert!(glitched(&document).is_empty());
+    }
+
+    #[test]
+    fn rejects_unglitching_creatures_that_arent_glitched() {
+        let mut document = parse(SAVE);
+
+        assert!(unglitch(&mut document, &Creature::Hookfish).is_err());
+        assert_eq!(glitched(&document), ["TrapClaw_Meta"]);
+    }
+}
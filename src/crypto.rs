@@ -1,23 +1,42 @@
+use crate::output::{
+    Output,
+    OutputMode,
+};
 use aes::Aes128;
 use anyhow::Result;
-use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::block_padding::{
+    Padding,
+    Pkcs7,
+};
 use aes::cipher::{
+    generic_array::GenericArray,
     BlockDecryptMut,
     BlockEncryptMut,
+    BlockSizeUser,
     KeyIvInit,
 };
-use std::io::prelude::*;
-use std::fs::{
-    File,
-    OpenOptions,
+use std::io::{
+    self,
+    prelude::*,
 };
+use std::fs::File;
 use std::str;
 
 type Aes128CbcDec = cbc::Decryptor<Aes128>;
 type Aes128CbcEnc = cbc::Encryptor<Aes128>;
+type BlockSize = <Aes128 as BlockSizeUser>::BlockSize;
+
+// AES block size in bytes, also the size of the key and IV
+pub const BLOCK_SIZE: usize = 16;
+
+// Amount of data to read from the underlying reader at once
+const CHUNK_SIZE: usize = 8 * 1024;
+
+// Filename used to refer to STDIN or STDOUT
+pub const STDIO: &str = "-";
 
 // Data encryption key
-const SAVEGAME_KEY: &[u8] = &[
+const SAVEGAME_KEY: [u8; BLOCK_SIZE] = [
     186, 173, 240, 13,
     0,   0,   0,   0,
     32,  48,  68,  194,
@@ -25,58 +44,324 @@ const SAVEGAME_KEY: &[u8] = &[
 ];
 
 // Encryption IV
-const SAVEGAME_IV: &[u8] = &[
+const SAVEGAME_IV: [u8; BLOCK_SIZE] = [
     229, 255, 255, 255,
     229, 186, 7,   0,
     186, 173, 240, 13,
     255, 0,   255, 0,
 ];
 
-// Read a file
-fn read_file(filename: &str) -> Result<Vec<u8>> {
-    let mut f      = File::open(filename)?;
-    let mut buffer = Vec::new();
+// Key and IV used by a particular build of the game.
+#[derive(Clone, Debug)]
+pub struct KeyProfile {
+    pub name: String,
+    key: [u8; BLOCK_SIZE],
+    iv: [u8; BLOCK_SIZE],
+}
+
+impl KeyProfile {
+    pub fn new(name: &str, key: [u8; BLOCK_SIZE], iv: [u8; BLOCK_SIZE]) -> Self {
+        Self {
+            name: name.to_string(),
+            key,
+            iv,
+        }
+    }
+
+    // The profile used by the Steam version of the game.
+    pub fn steam() -> Self {
+        Self::new("steam", SAVEGAME_KEY, SAVEGAME_IV)
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// Decrypts save data as it's read from the inner reader.
+pub struct DecryptReader<R> {
+    inner: R,
+    cipher: Aes128CbcDec,
+
+    // Ciphertext read from inner but not yet decrypted. The final block is
+    // always held back until EOF, since it contains the padding.
+    pending: Vec<u8>,
+
+    // Decrypted data waiting to be read, and our position within it.
+    decrypted: Vec<u8>,
+    position: usize,
+
+    eof: bool,
+}
+
+impl<R: Read> DecryptReader<R> {
+    pub fn new(inner: R, profile: &KeyProfile) -> Self {
+        let cipher = Aes128CbcDec::new(
+            &profile.key.into(),
+            &profile.iv.into(),
+        );
+
+        Self {
+            inner,
+            cipher,
+            pending: Vec::with_capacity(CHUNK_SIZE + BLOCK_SIZE),
+            decrypted: Vec::with_capacity(CHUNK_SIZE + BLOCK_SIZE),
+            position: 0,
+            eof: false,
+        }
+    }
 
-    f.read_to_end(&mut buffer)?;
+    // Decrypt the first `len` bytes of pending into the decrypted buffer.
+    fn decrypt_pending(&mut self, len: usize) {
+        for chunk in self.pending[..len].chunks_exact(BLOCK_SIZE) {
+            let mut block = GenericArray::clone_from_slice(chunk);
+            self.cipher.decrypt_block_mut(&mut block);
+            self.decrypted.extend_from_slice(&block);
+        }
 
-    Ok(buffer)
+        self.pending.drain(..len);
+    }
+
+    // Read and decrypt the next chunk of data. Returns false once there is
+    // nothing left to decrypt.
+    fn fill(&mut self) -> io::Result<bool> {
+        self.decrypted.clear();
+        self.position = 0;
+
+        while self.decrypted.is_empty() && !self.eof {
+            let mut chunk = [0; CHUNK_SIZE];
+            let read = self.inner.read(&mut chunk)?;
+
+            if read > 0 {
+                self.pending.extend_from_slice(&chunk[..read]);
+
+                // Decrypt everything except the final full block.
+                let full = self.pending.len() / BLOCK_SIZE * BLOCK_SIZE;
+                if full > BLOCK_SIZE {
+                    self.decrypt_pending(full - BLOCK_SIZE);
+                }
+
+                continue;
+            }
+
+            self.eof = true;
+
+            if self.pending.len() != BLOCK_SIZE {
+                let msg = "Data is not a multiple of the block size";
+                return Err(invalid_data(msg));
+            }
+
+            let mut block = GenericArray::clone_from_slice(&self.pending);
+            self.cipher.decrypt_block_mut(&mut block);
+            self.pending.clear();
+
+            let unpadded = <Pkcs7 as Padding<BlockSize>>::unpad(&block)
+                .map_err(|_| invalid_data("Invalid padding"))?;
+
+            self.decrypted.extend_from_slice(unpadded);
+        }
+
+        Ok(!self.decrypted.is_empty())
+    }
 }
 
-// Decrypt the save data
-fn decrypt(data: &[u8]) -> Result<Vec<u8>> {
-    let cipher    = Aes128CbcDec::new(SAVEGAME_KEY.into(), SAVEGAME_IV.into());
-    let decrypted = cipher.decrypt_padded_vec_mut::<Pkcs7>(data)?;
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.decrypted.len() && !self.fill()? {
+            return Ok(0);
+        }
+
+        let available = &self.decrypted[self.position..];
+        let len = available.len().min(buf.len());
 
-    Ok(decrypted)
+        buf[..len].copy_from_slice(&available[..len]);
+        self.position += len;
+
+        Ok(len)
+    }
 }
 
-// Read and decrypt a file
-pub fn decrypt_file(filename: &str) -> Result<Vec<u8>> {
-    let data      = read_file(filename)?;
-    let decrypted = decrypt(&data)?;
+// Encrypts save data as it's written to the inner writer. `finish` must be
+// called once all data is written, to write out the final padded block.
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    cipher: Aes128CbcEnc,
 
-    Ok(decrypted)
+    // Plaintext that doesn't yet fill a whole block.
+    pending: Vec<u8>,
 }
 
-// Encrypt a plain text file
-fn encrypt(data: &[u8]) -> Result<Vec<u8>> {
-    let cipher    = Aes128CbcEnc::new(SAVEGAME_KEY.into(), SAVEGAME_IV.into());
-    let encrypted = cipher.encrypt_padded_vec_mut::<Pkcs7>(data);
+impl<W: Write> EncryptWriter<W> {
+    pub fn new(inner: W, profile: &KeyProfile) -> Self {
+        let cipher = Aes128CbcEnc::new(
+            &profile.key.into(),
+            &profile.iv.into(),
+        );
+
+        Self {
+            inner,
+            cipher,
+            pending: Vec::with_capacity(BLOCK_SIZE),
+        }
+    }
 
-    Ok(encrypted)
+    // Pad and write the final block, returning the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let pos = self.pending.len();
+        let mut block = GenericArray::default();
+
+        block[..pos].copy_from_slice(&self.pending);
+        <Pkcs7 as Padding<BlockSize>>::pad(&mut block, pos);
+
+        self.cipher.encrypt_block_mut(&mut block);
+        self.inner.write_all(&block)?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
 }
 
-// Encrypt a given input and write it to the given output
-pub fn encrypt_file(input: &str, output: &str) -> Result<()> {
-    let mut output = OpenOptions::new()
-        .create_new(true)
-        .write(true)
-        .open(output)?;
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
 
-    let data = read_file(input)?;
-    let data = encrypt(&data)?;
+        let full = self.pending.len() / BLOCK_SIZE * BLOCK_SIZE;
+        let mut encrypted = Vec::with_capacity(full);
 
-    output.write_all(&data)?;
+        for chunk in self.pending[..full].chunks_exact(BLOCK_SIZE) {
+            let mut block = GenericArray::clone_from_slice(chunk);
+            self.cipher.encrypt_block_mut(&mut block);
+            encrypted.extend_from_slice(&block);
+        }
+
+        self.pending.drain(..full);
+        self.inner.write_all(&encrypted)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Open a file for reading, with "-" meaning STDIN
+pub fn open_input(filename: &str) -> Result<Box<dyn Read>> {
+    if filename == STDIO {
+        return Ok(Box::new(io::stdin()));
+    }
+
+    let fh = File::open(filename)?;
+
+    Ok(Box::new(fh))
+}
+
+// Encrypt the given data and write it to the output file
+pub fn write_encrypted_file(
+    output: &str,
+    mode: OutputMode,
+    data: &[u8],
+    profile: &KeyProfile,
+) -> Result<()> {
+    let mut writer = EncryptWriter::new(Output::create(output, mode)?, profile);
+
+    writer.write_all(data)?;
+    writer.finish()?.commit()?;
 
     Ok(())
 }
+
+// Encrypt a given input and write it to the given output
+pub fn encrypt_file(
+    input: &str,
+    output: &str,
+    mode: OutputMode,
+    profile: &KeyProfile,
+) -> Result<()> {
+    let mut reader = open_input(input)?;
+    let mut writer = EncryptWriter::new(Output::create(output, mode)?, profile);
+
+    io::copy(&mut reader, &mut writer)?;
+    writer.finish()?.commit()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypt(data: &[u8], profile: &KeyProfile) -> Vec<u8> {
+        let mut writer = EncryptWriter::new(Vec::new(), profile);
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn decrypt(data: &[u8], profile: &KeyProfile) -> io::Result<Vec<u8>> {
+        let mut reader = DecryptReader::new(data, profile);
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted)?;
+        Ok(decrypted)
+    }
+
+    #[test]
+    fn round_trips() {
+        let profile = KeyProfile::steam();
+
+        // Empty, partial blocks, exact blocks, and more than one chunk.
+        let lengths = [0, 1, 15, 16, 17, 32, CHUNK_SIZE, CHUNK_SIZE * 3 + 5];
+
+        for len in &lengths {
+            let data: Vec<u8> = (0..*len).map(|i| i as u8).collect();
+            let encrypted = encrypt(&data, &profile);
+
+            assert_eq!(encrypted.len(), (len / BLOCK_SIZE + 1) * BLOCK_SIZE);
+            assert_eq!(decrypt(&encrypted, &profile).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn round_trips_small_writes() {
+        let profile = KeyProfile::steam();
+        let data = b"<THSaveData>written a few bytes at a time</THSaveData>";
+
+        let mut writer = EncryptWriter::new(Vec::new(), &profile);
+        for chunk in data.chunks(3) {
+            writer.write_all(chunk).unwrap();
+        }
+        let encrypted = writer.finish().unwrap();
+
+        assert_eq!(encrypted, encrypt(data, &profile));
+        assert_eq!(decrypt(&encrypted, &profile).unwrap(), data);
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let profile = KeyProfile::steam();
+        let encrypted = encrypt(&[0; 100], &profile);
+
+        for len in &[1, 15, 17, encrypted.len() - 1] {
+            let error = decrypt(&encrypted[..*len], &profile).unwrap_err();
+
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn rejects_empty_data() {
+        let error = decrypt(&[], &KeyProfile::steam()).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_bad_padding() {
+        let encrypted = encrypt(b"<THSaveData/>", &KeyProfile::steam());
+        let wrong = KeyProfile::new("wrong", [0; BLOCK_SIZE], [0; BLOCK_SIZE]);
+
+        let error = decrypt(&encrypted, &wrong).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Invalid padding");
+    }
+}
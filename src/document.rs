@@ -0,0 +1,562 @@
+// document: A minimal XML document tree for editing raw save data.
+//
+// THSaveData only deserializes, and doesn't keep everything the game writes,
+// so edits are made to the XML itself. This keeps anything we don't know
+// about intact.
+use anyhow::{
+    anyhow,
+    Result,
+};
+use std::borrow::Cow;
+use std::str;
+use xml::attribute::OwnedAttribute;
+use xml::common::XmlVersion;
+use xml::name::OwnedName;
+use xml::namespace::Namespace;
+use xml::reader::{
+    ParserConfig,
+    XmlEvent as ReaderEvent,
+};
+use xml::writer::{
+    EmitterConfig,
+    XmlEvent as WriterEvent,
+};
+
+// Indentation used when pretty printing.
+const INDENT: &str = "  ";
+
+#[derive(Clone, Debug)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    CData(String),
+    Comment(String),
+    Whitespace(String),
+    ProcessingInstruction(String, Option<String>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Element {
+    pub name: OwnedName,
+    pub attributes: Vec<OwnedAttribute>,
+    pub namespace: Namespace,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: OwnedName::local(name),
+            attributes: Vec::new(),
+            namespace: Namespace::empty(),
+            children: Vec::new(),
+        }
+    }
+
+    // Creates an element containing only text.
+    pub fn with_text(name: &str, text: &str) -> Self {
+        let mut element = Self::new(name);
+        element.set_text(text);
+
+        element
+    }
+
+    // Builder style helper for adding a child element.
+    pub fn with_child(mut self, child: Element) -> Self {
+        self.children.push(Node::Element(child));
+        self
+    }
+
+    // Iterator over the child elements, ignoring text, comments, etc.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children
+            .iter()
+            .filter_map(|node| match node {
+                Node::Element(element) => Some(element),
+                _                      => None,
+            })
+    }
+
+    // Iterator over the child elements with the given name.
+    pub fn elements_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a Element> {
+        self.elements()
+            .filter(move |element| element.name.local_name == name)
+    }
+
+    // Mutable iterator over the child elements with the given name.
+    pub fn elements_named_mut<'a>(
+        &'a mut self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a mut Element> {
+        self.children
+            .iter_mut()
+            .filter_map(move |node| match node {
+                Node::Element(element) if element.name.local_name == name => {
+                    Some(element)
+                },
+                _ => None,
+            })
+    }
+
+    // Returns the first child element with the given name.
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements()
+            .find(|element| element.name.local_name == name)
+    }
+
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut Element> {
+        self.children
+            .iter_mut()
+            .find_map(|node| match node {
+                Node::Element(element) if element.name.local_name == name => {
+                    Some(element)
+                },
+                _ => None,
+            })
+    }
+
+    // Returns the text content of the element.
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|node| match node {
+                Node::Text(text) | Node::CData(text) => Some(text.as_str()),
+                _                                    => None,
+            })
+            .collect()
+    }
+
+    // Replaces the content of the element with the given text.
+    pub fn set_text(&mut self, text: &str) {
+        self.children = vec![Node::Text(text.to_string())];
+    }
+
+    // Removes child elements with the given name that match the predicate.
+    // Returns the number of elements removed.
+    pub fn remove_elements<F>(&mut self, name: &str, mut predicate: F) -> usize
+    where
+        F: FnMut(&Element) -> bool,
+    {
+        let before = self.children.len();
+
+        self.children.retain(|node| match node {
+            Node::Element(element) if element.name.local_name == name => {
+                !predicate(element)
+            },
+            _ => true,
+        });
+
+        // Removing elements leaves their indentation behind, tidy that up.
+        let removed = before - self.children.len();
+        if removed > 0 {
+            self.collapse_whitespace();
+        }
+
+        removed
+    }
+
+    // Inserts a child element. The game expects elements in a fixed order, so
+    // the new element goes after the last element with the same name. If
+    // there isn't one, it goes before the first element named in `before`,
+    // otherwise at the end.
+    pub fn insert_element(&mut self, mut child: Element, before: &[&str]) {
+        // Share our namespace so that no new declarations are written out.
+        child.set_namespace(&self.namespace);
+
+        let name = child.name.local_name.clone();
+        let position = self.children
+            .iter()
+            .rposition(|node| node.is_element_named(&name))
+            .map(|index| index + 1)
+            .or_else(|| {
+                self.children
+                    .iter()
+                    .position(|node| {
+                        before.iter().any(|name| node.is_element_named(name))
+                    })
+                    .map(|index| self.indentation_start(index))
+            });
+
+        let position = match position {
+            Some(position) => position,
+            None           => {
+                // Appending, step back over any trailing whitespace so that
+                // the closing tag stays where it was.
+                let end = self.children.len();
+                match self.children.last() {
+                    Some(Node::Whitespace(_)) => end - 1,
+                    _                         => end,
+                }
+            },
+        };
+
+        // Match the indentation of the surrounding elements.
+        let indent = self.children
+            .iter()
+            .find_map(|node| match node {
+                Node::Whitespace(space) => Some(space.clone()),
+                _                       => None,
+            });
+
+        match indent {
+            Some(indent) => {
+                self.children.insert(position, Node::Element(child));
+                self.children.insert(position, Node::Whitespace(indent));
+            },
+            None => {
+                self.children.insert(position, Node::Element(child));
+            },
+        }
+    }
+
+    // Sorts the child elements with the given name by key. The sorted
+    // elements take the places of the originals, so nothing else moves.
+    pub fn sort_elements_by_key<K, F>(&mut self, name: &str, key: F)
+    where
+        K: Ord,
+        F: FnMut(&Element) -> K,
+    {
+        self.sort_elements_where(|node| node.is_element_named(name), key);
+    }
+
+    // Sorts all of the child elements by key, leaving anything else in place.
+    pub fn sort_all_elements_by_key<K, F>(&mut self, key: F)
+    where
+        K: Ord,
+        F: FnMut(&Element) -> K,
+    {
+        self.sort_elements_where(|node| matches!(node, Node::Element(_)), key);
+    }
+
+    fn sort_elements_where<K, P, F>(&mut self, predicate: P, key: F)
+    where
+        K: Ord,
+        P: Fn(&Node) -> bool,
+        F: FnMut(&Element) -> K,
+    {
+        let positions: Vec<usize> = self.children
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| predicate(node))
+            .map(|(index, _)| index)
+            .collect();
+
+        let mut elements: Vec<Element> = positions
+            .iter()
+            .map(|&index| match &self.children[index] {
+                Node::Element(element) => element.clone(),
+                _                      => unreachable!(),
+            })
+            .collect();
+
+        elements.sort_by_cached_key(key);
+
+        for (index, element) in positions.into_iter().zip(elements) {
+            self.children[index] = Node::Element(element);
+        }
+    }
+
+    // Index of the whitespace preceding the node at index, if any.
+    fn indentation_start(&self, index: usize) -> usize {
+        match index.checked_sub(1).map(|i| &self.children[i]) {
+            Some(Node::Whitespace(_)) => index - 1,
+            _                         => index,
+        }
+    }
+
+    // Merges runs of whitespace nodes, keeping the last one.
+    fn collapse_whitespace(&mut self) {
+        let mut children: Vec<Node> = Vec::with_capacity(self.children.len());
+
+        for node in self.children.drain(..) {
+            if let (Some(Node::Whitespace(_)), Node::Whitespace(_)) =
+                (children.last(), &node)
+            {
+                children.pop();
+            }
+
+            children.push(node);
+        }
+
+        self.children = children;
+    }
+
+    // Re-indents the element and its children, with each child element on its
+    // own line. Elements containing text are left as they are.
+    fn indent(&mut self, depth: usize) {
+        let has_elements = self.children
+            .iter()
+            .any(|node| matches!(node, Node::Element(_)));
+
+        if !has_elements {
+            return;
+        }
+
+        let inner = format!("\n{}", INDENT.repeat(depth + 1));
+        let mut children = Vec::with_capacity(self.children.len() * 2 + 1);
+
+        for mut node in self.children.drain(..) {
+            if let Node::Whitespace(_) = node {
+                continue;
+            }
+
+            if let Node::Element(element) = &mut node {
+                element.indent(depth + 1);
+            }
+
+            children.push(Node::Whitespace(inner.clone()));
+            children.push(node);
+        }
+
+        children.push(Node::Whitespace(format!("\n{}", INDENT.repeat(depth))));
+
+        self.children = children;
+    }
+
+    fn set_namespace(&mut self, namespace: &Namespace) {
+        self.namespace = namespace.clone();
+
+        for node in &mut self.children {
+            if let Node::Element(element) = node {
+                element.set_namespace(namespace);
+            }
+        }
+    }
+}
+
+impl Node {
+    fn is_element_named(&self, name: &str) -> bool {
+        match self {
+            Self::Element(element) => element.name.local_name == name,
+            _                      => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Document {
+    version: XmlVersion,
+    encoding: String,
+    standalone: Option<bool>,
+    pub root: Element,
+}
+
+impl Document {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let reader = ParserConfig::new()
+            .trim_whitespace(false)
+            .whitespace_to_characters(false)
+            .cdata_to_characters(false)
+            .ignore_comments(false)
+            .coalesce_characters(true)
+            .create_reader(data);
+
+        let mut declaration = None;
+        let mut root = None;
+        let mut stack: Vec<Element> = Vec::new();
+
+        for event in reader {
+            let node = match event? {
+                ReaderEvent::StartDocument { version, encoding, standalone } => {
+                    declaration = Some((version, encoding, standalone));
+                    continue;
+                },
+                ReaderEvent::EndDocument => break,
+                ReaderEvent::StartElement { name, attributes, namespace } => {
+                    stack.push(Element {
+                        name,
+                        attributes,
+                        namespace,
+                        children: Vec::new(),
+                    });
+                    continue;
+                },
+                ReaderEvent::EndElement { .. } => {
+                    // The reader guarantees that elements are balanced.
+                    let element = stack.pop().unwrap();
+                    Node::Element(element)
+                },
+                ReaderEvent::Characters(text) => Node::Text(text),
+                ReaderEvent::CData(text)      => Node::CData(text),
+                ReaderEvent::Comment(text)    => Node::Comment(text),
+                ReaderEvent::Whitespace(text) => Node::Whitespace(text),
+                ReaderEvent::ProcessingInstruction { name, data } => {
+                    Node::ProcessingInstruction(name, data)
+                },
+            };
+
+            // Anything outside of the root element (comments, whitespace)
+            // is dropped.
+            match (stack.last_mut(), node) {
+                (Some(parent), node)          => parent.children.push(node),
+                (None, Node::Element(element)) => root = Some(element),
+                (None, _)                     => {},
+            }
+        }
+
+        let root = root.ok_or_else(|| anyhow!("XML has no root element"))?;
+        let (version, encoding, standalone) = declaration
+            .unwrap_or((XmlVersion::Version10, "utf-8".to_string(), None));
+
+        Ok(Self {
+            version,
+            encoding,
+            standalone,
+            root,
+        })
+    }
+
+    // Re-indents the whole document, so that it's easy to read and edit.
+    pub fn indent(&mut self) {
+        self.root.indent(0);
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        let mut writer = EmitterConfig::new()
+            .perform_indent(false)
+            .create_writer(&mut output);
+
+        writer.write(WriterEvent::StartDocument {
+            version: self.version,
+            encoding: Some(&self.encoding),
+            standalone: self.standalone,
+        })?;
+
+        writer.write(WriterEvent::Characters("\n"))?;
+        write_element(&mut writer, &self.root)?;
+        output.push(b'\n');
+
+        Ok(output)
+    }
+}
+
+fn write_element<W: std::io::Write>(
+    writer: &mut xml::EventWriter<W>,
+    element: &Element,
+) -> Result<()> {
+    let attributes: Vec<_> = element.attributes
+        .iter()
+        .map(|attribute| attribute.borrow())
+        .collect();
+
+    writer.write(WriterEvent::StartElement {
+        name: element.name.borrow(),
+        attributes: attributes.into(),
+        namespace: Cow::Borrowed(&element.namespace),
+    })?;
+
+    for node in &element.children {
+        match node {
+            Node::Element(child) => write_element(writer, child)?,
+            Node::Text(text) | Node::Whitespace(text) => {
+                writer.write(WriterEvent::Characters(text))?;
+            },
+            Node::CData(text) => writer.write(WriterEvent::CData(text))?,
+            Node::Comment(text) => writer.write(WriterEvent::Comment(text))?,
+            Node::ProcessingInstruction(name, data) => {
+                writer.write(WriterEvent::ProcessingInstruction {
+                    name,
+                    data: data.as_deref(),
+                })?;
+            },
+        }
+    }
+
+    writer.write(WriterEvent::EndElement {
+        name: Some(element.name.borrow()),
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::SAVE;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<Root xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <!-- kept -->
+  <A>1</A>
+  <A>2</A>
+  <C><![CDATA[<raw>]]></C>
+</Root>
+"#;
+
+    fn parse(xml: &str) -> Document {
+        Document::parse(xml.as_bytes()).unwrap()
+    }
+
+    fn to_string(document: &Document) -> String {
+        String::from_utf8(document.to_bytes().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn round_trips_unchanged() {
+        assert_eq!(to_string(&parse(XML)), XML);
+    }
+
+    #[test]
+    fn round_trips_save_data() {
+        // Namespace declarations are written in order of prefix, so the
+        // first pass may reorder them, but nothing else changes.
+        let once = to_string(&parse(SAVE));
+        let twice = to_string(&parse(&once));
+
+        assert_eq!(once, twice);
+        assert_eq!(once.lines().count(), SAVE.lines().count());
+    }
+
+    #[test]
+    fn inserts_after_elements_with_the_same_name() {
+        let mut document = parse(XML);
+        document.root.insert_element(Element::with_text("A", "3"), &["C"]);
+
+        assert_eq!(
+            to_string(&document),
+            XML.replace("<A>2</A>\n", "<A>2</A>\n  <A>3</A>\n"),
+        );
+    }
+
+    #[test]
+    fn inserts_before_following_elements() {
+        let mut document = parse(XML);
+        document.root.insert_element(Element::with_text("B", "1"), &["C"]);
+
+        assert_eq!(
+            to_string(&document),
+            XML.replace("  <C>", "  <B>1</B>\n  <C>"),
+        );
+    }
+
+    #[test]
+    fn appends_when_nothing_follows() {
+        let mut document = parse(XML);
+        document.root.insert_element(Element::with_text("D", "1"), &["E"]);
+
+        assert_eq!(
+            to_string(&document),
+            XML.replace("</C>\n", "</C>\n  <D>1</D>\n"),
+        );
+    }
+
+    #[test]
+    fn removes_elements_and_their_indentation() {
+        let mut document = parse(XML);
+        let removed = document.root
+            .remove_elements("A", |element| element.text() == "1");
+
+        assert_eq!(removed, 1);
+        assert_eq!(to_string(&document), XML.replace("  <A>1</A>\n", ""));
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        assert!(Document::parse(b"<Root><A></Root>").is_err());
+        assert!(Document::parse(b"").is_err());
+    }
+}
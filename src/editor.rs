@@ -0,0 +1,191 @@
+// editor: Edit text in the user's $EDITOR, until it passes validation.
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use std::env;
+use std::fs;
+use std::process::Command;
+use std::str;
+
+// Editor used when $EDITOR isn't set.
+const DEFAULT_EDITOR: &str = "vi";
+
+// Markers around the error shown at the top of the file. Everything between
+// them is removed before the text is validated.
+const ERROR_START: &str = "<!-- avsg: the save data is invalid";
+const ERROR_END: &str = "avsg: end of error -->\n";
+
+// Run the editor on the given file, waiting for it to exit.
+fn run_editor(path: &str) -> Result<()> {
+    let editor = env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+
+    // $EDITOR may contain arguments, eg. "code --wait".
+    let mut args = editor.split_whitespace();
+
+    // Checked for emptiness above, safe to unwrap
+    let program = args.next().unwrap();
+
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Couldn't run editor {}", editor))?;
+
+    if !status.success() {
+        return Err(anyhow!("Editor {} exited with {}", editor, status));
+    }
+
+    Ok(())
+}
+
+// Removes the error block that we added to the top of the text, if any.
+fn strip_error(text: &[u8]) -> &[u8] {
+    if !text.starts_with(ERROR_START.as_bytes()) {
+        return text;
+    }
+
+    text.windows(ERROR_END.len())
+        .position(|window| window == ERROR_END.as_bytes())
+        .map_or(text, |index| &text[index + ERROR_END.len()..])
+}
+
+fn with_error(text: &[u8], error: &str) -> Vec<u8> {
+    let header = format!(
+        "{}:\n\n  {}\n\nFix the problem and save to try again, or save an \
+         empty file to give up.\n{}",
+        ERROR_START,
+        error.replace("--", "- -"),
+        ERROR_END,
+    );
+
+    let mut output = header.into_bytes();
+    output.extend_from_slice(text);
+
+    output
+}
+
+// Opens text in the editor. Once the editor exits the text is checked with
+// validate, and the editor is reopened with the error at the top until it
+// passes. Returns None if the text wasn't changed, or was emptied.
+pub fn edit<F>(text: &[u8], mut validate: F) -> Result<Option<Vec<u8>>>
+where
+    F: FnMut(&[u8]) -> Result<()>,
+{
+    // Created only readable by the user, since it holds the decrypted save.
+    let file = tempfile::Builder::new()
+        .prefix("avsg")
+        .suffix(".xml")
+        .tempfile()?;
+
+    let path = file.path()
+        .to_str()
+        .ok_or_else(|| anyhow!("Temporary file path isn't valid UTF-8"))?
+        .to_string();
+
+    let mut contents = text.to_vec();
+
+    loop {
+        fs::write(&path, &contents)?;
+        run_editor(&path)?;
+
+        let edited = fs::read(&path)?;
+        let edited = strip_error(&edited);
+
+        if edited.iter().all(u8::is_ascii_whitespace) || edited == text {
+            return Ok(None);
+        }
+
+        match validate(edited) {
+            Ok(()) => return Ok(Some(edited.to_vec())),
+            Err(e) => {
+                eprintln!("Save data is invalid, reopening the editor");
+                contents = with_error(edited, &e.to_string());
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &[u8] = b"<?xml version=\"1.0\"?>\n<THSaveData/>\n";
+
+    #[test]
+    fn strips_the_error() {
+        let text = with_error(XML, "missing field `mDifficulty`");
+
+        assert!(text.starts_with(ERROR_START.as_bytes()));
+        assert_eq!(strip_error(&text), XML);
+    }
+
+    #[test]
+    fn leaves_text_without_an_error() {
+        assert_eq!(strip_error(XML), XML);
+        assert_eq!(strip_error(b""), b"");
+    }
+
+    #[test]
+    fn leaves_text_if_the_error_end_was_removed() {
+        let text = with_error(XML, "broken");
+        let end = text.len() - XML.len() - ERROR_END.len();
+        let mut edited = text[..end].to_vec();
+        edited.extend_from_slice(XML);
+
+        assert_eq!(strip_error(&edited), &edited[..]);
+    }
+
+    #[test]
+    fn keeps_the_error_inside_the_comment() {
+        // "--" can't appear inside an XML comment.
+        let text = with_error(XML, "expected --> found --");
+        let header = &text[..text.len() - XML.len()];
+        let header = str::from_utf8(header).unwrap();
+
+        assert_eq!(header.matches("--").count(), 2);
+        assert_eq!(strip_error(&text), XML);
+    }
+
+    #[test]
+    fn replaces_the_error_on_each_attempt() {
+        let once = with_error(XML, "first");
+        let twice = with_error(strip_error(&once), "second");
+
+        assert_eq!(twice.windows(4).filter(|w| *w == b"avsg").count(), 2);
+        assert_eq!(strip_error(&twice), XML);
+    }
+
+    // Runs the whole edit loop, with sed standing in for the user. This is
+    // the only test that sets $EDITOR.
+    #[test]
+    fn edits_until_valid() {
+        env::set_var("EDITOR", "sed -i s/@/#/");
+
+        // Unchanged text is given up on.
+        assert!(edit(XML, |_| Ok(())).unwrap().is_none());
+
+        // The first attempt fails, so the editor is reopened with the error
+        // at the top. None of it should be left in the saved text.
+        let mut attempts = 0;
+        let edited = edit(b"<A>@</A>\n", |text| {
+            attempts += 1;
+
+            assert!(!text.starts_with(ERROR_START.as_bytes()));
+
+            if attempts == 1 {
+                Err(anyhow!("not yet -- try again"))
+            }
+            else {
+                Ok(())
+            }
+        });
+
+        assert_eq!(edited.unwrap().unwrap(), b"<A>#</A>\n");
+        assert_eq!(attempts, 2);
+    }
+}
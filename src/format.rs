@@ -0,0 +1,163 @@
+// format: Pretty print save data XML, optionally in a canonical order.
+//
+// The game writes some collections in the order that things happened, which
+// makes two otherwise identical saves look different. Sorting them gives the
+// same text for the same content, which makes saves easy to diff.
+use crate::document::{
+    Document,
+    Element,
+};
+use std::str;
+
+// What to sort a collection by.
+#[derive(Clone, Copy, Debug)]
+enum SortBy {
+    // The text of a child element.
+    Child(&'static str),
+
+    // The element's own name, for dictionaries keyed by element name.
+    Name,
+
+    // The element's own text.
+    Text,
+}
+
+// Collections that the game doesn't care about the order of. Paths are
+// relative to the root element, with a final * meaning every child element.
+const UNORDERED: &[(&str, SortBy)] = &[
+    ("THItemRecord",       SortBy::Child("mName")),
+    ("KeyPoint",           SortBy::Text),
+    ("AreaSaveData/mItem", SortBy::Text),
+    ("mRandomItem/*",      SortBy::Name),
+    ("CreatureGlitched",   SortBy::Text),
+];
+
+fn sort_key(element: &Element, by: SortBy) -> String {
+    match by {
+        SortBy::Child(child) => {
+            element.child(child)
+                .map(Element::text)
+                .unwrap_or_default()
+        },
+        SortBy::Name => element.name.local_name.clone(),
+        SortBy::Text => element.text(),
+    }
+}
+
+// Sorts the elements at path, below the given element.
+fn sort_path(element: &mut Element, path: &str, by: SortBy) {
+    match path.split_once('/') {
+        Some((name, rest)) => {
+            for child in element.elements_named_mut(name) {
+                sort_path(child, rest, by);
+            }
+        },
+        None if path == "*" => {
+            element.sort_all_elements_by_key(|child| sort_key(child, by));
+        },
+        None => {
+            element.sort_elements_by_key(path, |child| sort_key(child, by));
+        },
+    }
+}
+
+// Re-indent the document, sorting unordered collections if asked to.
+pub fn format(document: &mut Document, sort: bool) {
+    if sort {
+        for (path, by) in UNORDERED {
+            sort_path(&mut document.root, path, *by);
+        }
+    }
+
+    document.indent();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::save_with;
+
+    const ITEMS: &str = "<mItem>AxiomDisruptor</mItem>";
+    const KEY_POINTS: &str = "<KeyPoint>GotAxiomDisruptor</KeyPoint>
+  <KeyPoint>XedurDefeated</KeyPoint>";
+    const CREATURES: &str =
+        "<CreatureGlitched>TrapClaw_Meta</CreatureGlitched>";
+
+    // The test save with randomizer data and the given collections.
+    fn save(
+        items: &str,
+        key_points: &str,
+        random: &str,
+        creatures: &str,
+    ) -> String {
+        let random = format!(
+            "<mRandomItem>{}</mRandomItem>\n  <mUseRealTimers>",
+            random,
+        );
+
+        save_with(&[
+            (ITEMS, items),
+            (KEY_POINTS, key_points),
+            ("<mUseRealTimers>", &random),
+            (CREATURES, creatures),
+        ])
+    }
+
+    fn format_xml(xml: &str, sort: bool) -> String {
+        let mut document = Document::parse(xml.as_bytes()).unwrap();
+        format(&mut document, sort);
+
+        String::from_utf8(document.to_bytes().unwrap()).unwrap()
+    }
+
+    fn ordered() -> String {
+        save(
+            "<mItem>AxiomDisruptor</mItem><mItem>Kilver</mItem>",
+            "<KeyPoint>GotAxiomDisruptor</KeyPoint>
+  <KeyPoint>XedurDefeated</KeyPoint>",
+            "<Kilver>Nova</Kilver><Nova>Kilver</Nova>",
+            "<CreatureGlitched>Blite</CreatureGlitched>
+  <CreatureGlitched>TrapClaw_Meta</CreatureGlitched>",
+        )
+    }
+
+    fn shuffled() -> String {
+        save(
+            "<mItem>Kilver</mItem><mItem>AxiomDisruptor</mItem>",
+            "<KeyPoint>XedurDefeated</KeyPoint>
+  <KeyPoint>GotAxiomDisruptor</KeyPoint>",
+            "<Nova>Kilver</Nova><Kilver>Nova</Kilver>",
+            "<CreatureGlitched>TrapClaw_Meta</CreatureGlitched>
+  <CreatureGlitched>Blite</CreatureGlitched>",
+        )
+    }
+
+    #[test]
+    fn same_content_formats_the_same() {
+        assert_eq!(format_xml(&shuffled(), true), format_xml(&ordered(), true));
+    }
+
+    #[test]
+    fn sorting_is_idempotent() {
+        let once = format_xml(&shuffled(), true);
+
+        assert_eq!(format_xml(&once, true), once);
+    }
+
+    #[test]
+    fn sorts_nested_collections() {
+        let sorted = format_xml(&shuffled(), true);
+        let position = |text| sorted.find(text).unwrap();
+
+        assert!(position("<mItem>AxiomDisruptor") < position("<mItem>Kilver"));
+        assert!(position("<Kilver>") < position("<Nova>"));
+    }
+
+    #[test]
+    fn keeps_order_without_sort() {
+        let formatted = format_xml(&shuffled(), false);
+
+        assert_ne!(formatted, format_xml(&ordered(), false));
+        assert_eq!(formatted, format_xml(&formatted, false));
+    }
+}
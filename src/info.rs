@@ -0,0 +1,57 @@
+// info: Summary of a save game, including both of its clocks.
+use crate::achievements::Achievements;
+use crate::clock::{
+    frames_to_duration,
+    Clock,
+};
+use crate::savedata::THSaveData;
+
+pub struct Info<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> Info<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    pub fn report(&self) {
+        let savedata = self.savedata;
+        let game_time = frames_to_duration(Clock::Game.frames(savedata));
+        let real_time = frames_to_duration(Clock::Real.frames(savedata));
+        let timer = Clock::for_savedata(savedata);
+
+        let (items, total_items, items_percent) = Achievements::new(savedata)
+            .item_counts();
+
+        let screens = savedata.screen_count;
+        let total_screens = savedata.total_screen_count;
+        let screens_percent = if total_screens > 0 {
+            screens as f32 / total_screens as f32 * 100.0
+        }
+        else {
+            0.0
+        };
+
+        println!("Save Info:");
+        println!("  - Game time: {}", game_time);
+        println!("  - Real time: {}", real_time);
+        println!("  - In-game timer: {}", timer);
+        println!("  - Deaths: {}", savedata.num_deaths);
+        println!(
+            "  - Items: {}/{} ({:.2}%)",
+            items,
+            total_items,
+            items_percent,
+        );
+        println!(
+            "  - Map: {}/{} screens ({:.2}%)",
+            screens,
+            total_screens,
+            screens_percent,
+        );
+        println!("Overclocked and Speedrunner are timed with game time.");
+    }
+}
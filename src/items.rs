@@ -0,0 +1,241 @@
+// items: Catalogue of the named items found around Sudra.
+
+// Areas in the order they're usually visited.
+pub const AREAS: &[&str] = &[
+    "Eribu",
+    "Absu",
+    "Zi",
+    "Kur",
+    "Indi",
+    "Ukkin-Na",
+    "Edin",
+    "Mar-Uru",
+    "E-Kur-Mah",
+];
+
+#[derive(Debug)]
+pub struct CatalogueItem {
+    // Internal ID, as it appears in the save data.
+    pub id: &'static str,
+
+    // Name as shown in game.
+    pub name: &'static str,
+
+    // Area the item is found in during a normal game.
+    pub area: &'static str,
+}
+
+// Named items, based on https://axiom-verge.fandom.com/wiki/Category:Items
+// Nodes, fragments and notes are nameless in the save data, so aren't listed.
+pub const CATALOGUE: &[CatalogueItem] = &[
+    // Weapons
+    CatalogueItem {
+        id: "AxiomDisruptor",
+        name: "Axiom Disruptor",
+        area: "Eribu",
+    },
+    CatalogueItem {
+        id: "Nova",
+        name: "Nova",
+        area: "Eribu",
+    },
+    CatalogueItem {
+        id: "MultiDisruptor",
+        name: "Multi-Disruptor",
+        area: "Eribu",
+    },
+    CatalogueItem {
+        id: "Shards",
+        name: "Shards",
+        area: "Eribu",
+    },
+    CatalogueItem {
+        id: "Kilver",
+        name: "Kilver",
+        area: "Absu",
+    },
+    CatalogueItem {
+        id: "Firewall",
+        name: "Firewall",
+        area: "Absu",
+    },
+    CatalogueItem {
+        id: "ReverseSlicer",
+        name: "Reverse Slicer",
+        area: "Absu",
+    },
+    CatalogueItem {
+        id: "TurbinePulse",
+        name: "Turbine Pulse",
+        area: "Absu",
+    },
+    CatalogueItem {
+        id: "ScissorBeam",
+        name: "Scissor Beam",
+        area: "Absu",
+    },
+    CatalogueItem {
+        id: "LightningGun",
+        name: "Lightning Gun",
+        area: "Zi",
+    },
+    CatalogueItem {
+        id: "IonBeam",
+        name: "Ion Beam",
+        area: "Zi",
+    },
+    CatalogueItem {
+        id: "Flamethrower",
+        name: "Flamethrower",
+        area: "Kur",
+    },
+    CatalogueItem {
+        id: "InertialPulse",
+        name: "Inertial Pulse",
+        area: "Kur",
+    },
+    CatalogueItem {
+        id: "Voranj",
+        name: "Voranj",
+        area: "Indi",
+    },
+    CatalogueItem {
+        id: "QuantumVariegator",
+        name: "Quantum Variegator",
+        area: "Indi",
+    },
+    CatalogueItem {
+        id: "HeatSeeker",
+        name: "Heat Seeker",
+        area: "Ukkin-Na",
+    },
+    CatalogueItem {
+        id: "TetheredCharge",
+        name: "Tethered Charge",
+        area: "Ukkin-Na",
+    },
+    CatalogueItem {
+        id: "OrbitalDischarge",
+        name: "Orbital Discharge",
+        area: "Edin",
+    },
+    CatalogueItem {
+        id: "Reflector",
+        name: "Reflector",
+        area: "Edin",
+    },
+    CatalogueItem {
+        id: "HypoAtomizer",
+        name: "Hypo-Atomizer",
+        area: "Mar-Uru",
+    },
+
+    // Tools
+    CatalogueItem {
+        id: "PasscodeTool",
+        name: "Passcode Tool",
+        area: "Eribu",
+    },
+    CatalogueItem {
+        id: "AddressDisruptor",
+        name: "Address Disruptor",
+        area: "Absu",
+    },
+    CatalogueItem {
+        id: "LaserDrill",
+        name: "Laser Drill",
+        area: "Absu",
+    },
+    CatalogueItem {
+        id: "RemoteDrone",
+        name: "Remote Drone",
+        area: "Zi",
+    },
+    CatalogueItem {
+        id: "FieldDisruptor",
+        name: "Field Disruptor",
+        area: "Zi",
+    },
+    CatalogueItem {
+        id: "AddressBomb",
+        name: "Address Bomb",
+        area: "Indi",
+    },
+    CatalogueItem {
+        id: "Grapple",
+        name: "Grapple",
+        area: "Kur",
+    },
+    CatalogueItem {
+        id: "EnhancedLaunch",
+        name: "Enhanced Drone Launch",
+        area: "Ukkin-Na",
+    },
+    CatalogueItem {
+        id: "AddressDisruptor2",
+        name: "Address Disruptor 2",
+        area: "Edin",
+    },
+    CatalogueItem {
+        id: "DroneTeleport",
+        name: "Drone Teleport",
+        area: "Mar-Uru",
+    },
+
+    // Permanent upgrades
+    CatalogueItem {
+        id: "HighJump",
+        name: "High Jump",
+        area: "Absu",
+    },
+    CatalogueItem {
+        id: "LabCoat",
+        name: "Modified Lab Coat",
+        area: "Kur",
+    },
+    CatalogueItem {
+        id: "BiofluxAccelerator1",
+        name: "Bioflux Accelerator",
+        area: "Indi",
+    },
+    CatalogueItem {
+        id: "TrenchCoat",
+        name: "Trenchcoat",
+        area: "Edin",
+    },
+    CatalogueItem {
+        id: "BiofluxAccelerator2",
+        name: "Bioflux Accelerator 2",
+        area: "Mar-Uru",
+    },
+    CatalogueItem {
+        id: "RedCoat",
+        name: "Red Coat",
+        area: "E-Kur-Mah",
+    },
+];
+
+// Find an item in the catalogue by its internal ID.
+pub fn lookup(id: &str) -> Option<&'static CatalogueItem> {
+    CATALOGUE
+        .iter()
+        .find(|item| item.id == id)
+}
+
+// Returns the in game name for an item ID, falling back to the ID itself for
+// items that we don't know about.
+pub fn display_name(id: &str) -> &str {
+    match lookup(id) {
+        Some(item) => item.name,
+        None       => id,
+    }
+}
+
+// Returns the position of an area in AREAS, used for sorting. Unknown areas
+// sort last.
+pub fn area_order(area: &str) -> usize {
+    AREAS
+        .iter()
+        .position(|&a| a == area)
+        .unwrap_or(AREAS.len())
+}
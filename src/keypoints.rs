@@ -0,0 +1,231 @@
+// keypoints: Story milestones, recorded as key points in the save data.
+//
+// The game records a key point as each part of the story is completed. None
+// of the key point IDs below have been confirmed from real saves yet. They
+// follow the naming used for the other save fields, Got<item ID> for items
+// and <boss>Defeated for bosses, so could be wrong. Milestones are also done
+// by the item being collected, or the boss's speedrun checkpoint, so they
+// don't rely on the key point IDs alone. Key points that aren't recognised
+// are listed by `avsg progress`, so that real IDs can be found and added.
+use crate::savedata::THSaveData;
+
+#[derive(Debug)]
+pub struct Milestone {
+    // Key points that mark the milestone as done. Where the spelling isn't
+    // known, each possible name is listed.
+    pub key_points: &'static [&'static str],
+
+    // Description of the milestone.
+    pub name: &'static str,
+
+    // Item collected at this milestone. Not every item is recorded as a key
+    // point, so the item itself is checked for too.
+    pub item: Option<&'static str>,
+
+    // Speedrun checkpoint recorded at this milestone, named after the boss.
+    pub checkpoint: Option<&'static str>,
+}
+
+impl Milestone {
+    pub fn is_key_point(&self, key_point: &str) -> bool {
+        self.key_points.contains(&key_point)
+    }
+
+    // Whether the save has the milestone's key point, item or checkpoint.
+    pub fn is_done(&self, savedata: &THSaveData) -> bool {
+        let key_point = savedata.key_points_completed
+            .iter()
+            .any(|key_point| self.is_key_point(key_point));
+
+        let item = self.item.is_some_and(|id| {
+            savedata.items
+                .iter()
+                .any(|item| item.name == id)
+        });
+
+        let checkpoint = self.checkpoint.is_some_and(|name| {
+            savedata.speedrun_checkpoints
+                .iter()
+                .flatten()
+                .any(|checkpoint| checkpoint.name == name)
+        });
+
+        key_point || item || checkpoint
+    }
+}
+
+// Main story milestones, in the order they're usually completed. Optional
+// milestones, such as defeating the Clone, aren't listed.
+pub const MILESTONES: &[Milestone] = &[
+    Milestone {
+        key_points: &["GotAxiomDisruptor"],
+        name: "Found the Axiom Disruptor",
+        item: Some("AxiomDisruptor"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["XedurDefeated"],
+        name: "Defeated Xedur",
+        item: None,
+        checkpoint: Some("Xedur"),
+    },
+    Milestone {
+        key_points: &["GotAddressDisruptor"],
+        name: "Found the Address Disruptor",
+        item: Some("AddressDisruptor"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["GotLaserDrill"],
+        name: "Found the Laser Drill",
+        item: Some("LaserDrill"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["TelalDefeated"],
+        name: "Defeated Telal",
+        item: None,
+        checkpoint: Some("Telal"),
+    },
+    Milestone {
+        key_points: &["GotRemoteDrone"],
+        name: "Found the Remote Drone",
+        item: Some("RemoteDrone"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["UrukuDefeated"],
+        name: "Defeated Uruku",
+        item: None,
+        checkpoint: Some("Uruku"),
+    },
+    Milestone {
+        key_points: &["GotGrapple"],
+        name: "Found the Grapple",
+        item: Some("Grapple"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["GotLabCoat"],
+        name: "Found Trace's Modified Lab Coat",
+        item: Some("LabCoat"),
+        checkpoint: None,
+    },
+    Milestone {
+        // Unconfirmed which spelling the game uses, so both are checked.
+        key_points: &["GirTabDefeated", "Gir-TabDefeated"],
+        name: "Defeated Gir-Tab",
+        item: None,
+        checkpoint: Some("Gir-Tab"),
+    },
+    Milestone {
+        key_points: &["GotTrenchCoat"],
+        name: "Found the Trenchcoat",
+        item: Some("TrenchCoat"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["VisionDefeated"],
+        name: "Defeated the Vision",
+        item: None,
+        checkpoint: Some("Vision"),
+    },
+    Milestone {
+        key_points: &["GotAddressDisruptor2"],
+        name: "Found Address Disruptor 2",
+        item: Some("AddressDisruptor2"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["UkhuDefeated"],
+        name: "Defeated Ukhu",
+        item: None,
+        checkpoint: Some("Ukhu"),
+    },
+    Milestone {
+        key_points: &["GotDroneTeleport"],
+        name: "Found the Drone Teleport",
+        item: Some("DroneTeleport"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["GotRedCoat"],
+        name: "Found the Red Coat",
+        item: Some("RedCoat"),
+        checkpoint: None,
+    },
+    Milestone {
+        key_points: &["SentinelDefeated"],
+        name: "Defeated the Sentinel",
+        item: None,
+        checkpoint: Some("Sentinel"),
+    },
+];
+
+// Key points that we know about, but that aren't part of the main story.
+pub const OPTIONAL: &[(&str, &str)] = &[
+    ("CloneDefeated", "Defeated the Clone"),
+];
+
+// Finds the description of a key point, for any key point that we know.
+pub fn describe(key_point: &str) -> Option<&'static str> {
+    MILESTONES
+        .iter()
+        .find(|milestone| milestone.is_key_point(key_point))
+        .map(|milestone| milestone.name)
+        .or_else(|| {
+            OPTIONAL
+                .iter()
+                .find(|(id, _)| *id == key_point)
+                .map(|(_, name)| *name)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{
+        save_with,
+        savedata,
+    };
+
+    fn milestone(name: &str) -> &'static Milestone {
+        MILESTONES
+            .iter()
+            .find(|milestone| milestone.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn done_from_key_points_and_items() {
+        let savedata = savedata(&save_with(&[]));
+
+        assert!(milestone("Defeated Xedur").is_done(&savedata));
+        assert!(milestone("Found the Address Disruptor").is_done(&savedata));
+        assert!(!milestone("Defeated Telal").is_done(&savedata));
+    }
+
+    #[test]
+    fn done_from_checkpoints() {
+        let xml = save_with(&[(
+            "<CreatureGlitched>",
+            "<SpeedrunCheckpoint><mName>Telal</mName><mFrames>100000</mFrames>\
+             </SpeedrunCheckpoint>\n  <CreatureGlitched>",
+        )]);
+        let savedata = savedata(&xml);
+
+        assert!(milestone("Defeated Telal").is_done(&savedata));
+        assert!(!milestone("Defeated Uruku").is_done(&savedata));
+    }
+
+    #[test]
+    fn boss_milestones_have_checkpoints() {
+        for milestone in MILESTONES {
+            let boss = milestone.key_points
+                .iter()
+                .any(|key_point| key_point.ends_with("Defeated"));
+
+            assert_eq!(boss, milestone.checkpoint.is_some(), "{:?}", milestone);
+        }
+    }
+}
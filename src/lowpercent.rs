@@ -0,0 +1,458 @@
+// lowpercent: Plan a Low % run, keeping item collection under the limit.
+use crate::achievements::{
+    Achievements,
+    ACHIEVEMENT_LOW_PERCENT,
+};
+use crate::items;
+use crate::savedata::{
+    THItemRecord,
+    THItemType,
+    THSaveData,
+};
+use std::collections::{
+    BTreeSet,
+    VecDeque,
+};
+use std::str;
+
+// An item that the game can't be finished without.
+struct Gate {
+    id: &'static str,
+    type_: THItemType,
+
+    // What the item is needed for.
+    reason: &'static str,
+
+    // Items that must be collected before this one is any use. These are
+    // gates themselves.
+    requires: &'static [&'static str],
+}
+
+// Items that the game can't be finished without. Anything else is optional
+// for a Low % run, unless the save records one of these as needing it.
+const GATES: &[Gate] = &[
+    Gate {
+        id: "AxiomDisruptor",
+        type_: THItemType::Weapon,
+        reason: "Starting weapon, needed to fight Xedur",
+        requires: &[],
+    },
+    Gate {
+        id: "AddressDisruptor",
+        type_: THItemType::Tool,
+        reason: "Glitches the blocks out of Absu",
+        requires: &[],
+    },
+    Gate {
+        id: "LaserDrill",
+        type_: THItemType::Tool,
+        reason: "Drills through the rock in Absu",
+        requires: &[],
+    },
+    Gate {
+        id: "HighJump",
+        type_: THItemType::PermanentUpgrade,
+        reason: "Reaches the upper ledges in Absu and Zi",
+        requires: &[],
+    },
+    Gate {
+        id: "RemoteDrone",
+        type_: THItemType::Tool,
+        reason: "Fits through the narrow tunnels in Zi",
+        requires: &[],
+    },
+    Gate {
+        id: "Grapple",
+        type_: THItemType::Tool,
+        reason: "Crosses the gaps in Kur",
+        requires: &[],
+    },
+    Gate {
+        id: "LabCoat",
+        type_: THItemType::PermanentUpgrade,
+        reason: "Phases through thin walls",
+        requires: &[],
+    },
+    Gate {
+        id: "EnhancedLaunch",
+        type_: THItemType::Tool,
+        reason: "Launches the drone up the shafts in Ukkin-Na",
+        requires: &["RemoteDrone"],
+    },
+    Gate {
+        id: "TrenchCoat",
+        type_: THItemType::PermanentUpgrade,
+        reason: "Phases through thicker walls",
+        requires: &["LabCoat"],
+    },
+    Gate {
+        id: "AddressDisruptor2",
+        type_: THItemType::Tool,
+        reason: "Glitches the blocks leading to Mar-Uru",
+        requires: &["AddressDisruptor"],
+    },
+    Gate {
+        id: "DroneTeleport",
+        type_: THItemType::Tool,
+        reason: "Teleports past the barriers to E-Kur-Mah",
+        requires: &["RemoteDrone"],
+    },
+    Gate {
+        id: "RedCoat",
+        type_: THItemType::PermanentUpgrade,
+        reason: "Phases through the walls in E-Kur-Mah",
+        requires: &["TrenchCoat"],
+    },
+];
+
+// Fragments only count towards the item percentage once a node is complete.
+const FRAGMENTS_PER_NODE: f32 = 5.0;
+
+// The most items that can be collected out of the total while staying under
+// the Low % limit. Uses the same sum as the achievement, so that the two
+// always agree.
+fn item_limit(total: i32) -> usize {
+    let under_limit = |items: i32| {
+        items as f32 / total as f32 * 100.0 < ACHIEVEMENT_LOW_PERCENT
+    };
+
+    (0..=total)
+        .rev()
+        .find(|&items| under_limit(items))
+        .unwrap_or(0) as usize
+}
+
+// A required item, and why it's required.
+struct Requirement {
+    id: String,
+
+    // The gate this item is, if any. Other items are only required because
+    // the save records a required item as needing them.
+    gate: Option<&'static Gate>,
+
+    // The required item that needs this one, for items that aren't gates.
+    needed_by: Option<String>,
+
+    // Items needed before this one, from the gate and the save's item record.
+    requires: Vec<String>,
+
+    // Known from the gate or the save's item record.
+    type_: Option<THItemType>,
+
+    collected: bool,
+}
+
+pub struct LowPercent<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> LowPercent<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    fn record(&self, id: &str) -> Option<&'a THItemRecord> {
+        self.savedata.items
+            .iter()
+            .find(|item| item.name == id)
+    }
+
+    // The minimum set of items needed to finish the game, and whether
+    // they've been collected yet. Starts from the gates, and adds the items
+    // that each required item's record says it needs.
+    fn requirements(&self) -> Vec<Requirement> {
+        let mut requirements: Vec<Requirement> = Vec::new();
+        let mut queue: VecDeque<(String, Option<String>)> = GATES
+            .iter()
+            .map(|gate| (gate.id.to_string(), None))
+            .collect();
+
+        while let Some((id, needed_by)) = queue.pop_front() {
+            if requirements.iter().any(|requirement| requirement.id == id) {
+                continue;
+            }
+
+            let gate = GATES.iter().find(|gate| gate.id == id);
+            let record = self.record(&id);
+
+            let mut requires: Vec<String> = gate
+                .map(|gate| gate.requires)
+                .unwrap_or_default()
+                .iter()
+                .map(|id| id.to_string())
+                .collect();
+
+            let saved = record.and_then(|item| item.required_item.as_ref());
+            if let Some(required) = saved {
+                if !requires.contains(required) {
+                    requires.push(required.clone());
+                }
+            }
+
+            for required in &requires {
+                queue.push_back((required.clone(), Some(id.clone())));
+            }
+
+            requirements.push(Requirement {
+                gate,
+                needed_by: gate.map_or(needed_by, |_| None),
+                requires,
+                type_: gate
+                    .map(|gate| gate.type_)
+                    .or_else(|| record.map(|item| item.type_)),
+                collected: record.is_some(),
+                id,
+            });
+        }
+
+        requirements
+    }
+
+    // Prints the items needed to finish the game, against what's been
+    // collected, and how many optional items can still be picked up while
+    // staying under the Low % limit.
+    pub fn plan(&self) {
+        let achievements = Achievements::new(self.savedata);
+        let (current, needed, percent) = achievements.item_counts();
+
+        let limit = item_limit(needed);
+
+        println!(
+            "Low % Plan: {}/{} items ({:.2}%), at most {} items to stay \
+             under {}%",
+            current,
+            needed,
+            percent,
+            limit,
+            ACHIEVEMENT_LOW_PERCENT,
+        );
+
+        let requirements = self.requirements();
+        let required: BTreeSet<&str> = requirements
+            .iter()
+            .map(|requirement| requirement.id.as_str())
+            .collect();
+
+        let collected = requirements
+            .iter()
+            .filter(|requirement| requirement.collected)
+            .count();
+
+        println!(
+            "Required items: {}/{} collected",
+            collected,
+            requirements.len(),
+        );
+
+        for requirement in &requirements {
+            let status = if requirement.collected {
+                "Collected"
+            }
+            else {
+                "Not collected"
+            };
+
+            println!(
+                "  - {}: {} ({})",
+                items::display_name(&requirement.id),
+                status,
+                reason(requirement),
+            );
+        }
+
+        let optional: Vec<&THItemRecord> = self.savedata.items
+            .iter()
+            .filter(|item| counts_towards_total(item))
+            .filter(|item| !required.contains(item.name.as_str()))
+            .collect();
+
+        println!("Non-required pickups: {}", optional.len());
+
+        for item in optional {
+            println!(
+                "  - {} ({}){}",
+                items::display_name(&item.name),
+                item.type_,
+                fragment_note(item),
+            );
+        }
+
+        let spent = current + remaining(&requirements);
+        if spent <= limit {
+            println!(
+                "Headroom: {} more optional items, after collecting the \
+                 required items",
+                limit - spent,
+            );
+        }
+        else {
+            println!(
+                "Headroom: None, {} items over the limit after collecting the \
+                 required items",
+                spent - limit,
+            );
+        }
+    }
+}
+
+// Required items still to collect that will raise the percentage. Items of
+// an unknown type are assumed to count.
+fn remaining(requirements: &[Requirement]) -> usize {
+    requirements
+        .iter()
+        .filter(|requirement| !requirement.collected)
+        .filter(|requirement| requirement.type_.is_none_or(type_counts))
+        .count()
+}
+
+// Why an item is needed, including what it depends on.
+fn reason(requirement: &Requirement) -> String {
+    let reason = match (requirement.gate, &requirement.needed_by) {
+        (Some(gate), _)         => gate.reason.to_string(),
+        (None, Some(needed_by)) => {
+            format!("Needed for {}", items::display_name(needed_by))
+        },
+        (None, None)            => "Required".to_string(),
+    };
+
+    let requires: Vec<&str> = requirement.requires
+        .iter()
+        .map(|id| items::display_name(id))
+        .collect();
+
+    if requires.is_empty() {
+        reason
+    }
+    else {
+        format!("{}, needs {}", reason, requires.join(" and "))
+    }
+}
+
+// Types of item that count towards the item percentage.
+fn type_counts(type_: THItemType) -> bool {
+    !matches!(type_, THItemType::GlitchBombDrop | THItemType::HealthDrop)
+}
+
+// Items that count towards the item percentage.
+fn counts_towards_total(item: &THItemRecord) -> bool {
+    !item.excluded_from_count && type_counts(item.type_)
+}
+
+fn fragment_note(item: &THItemRecord) -> String {
+    match item.type_ {
+        THItemType::HealthNodeFragment | THItemType::PowerNodeFragment => {
+            format!(", 1/{} of an item", FRAGMENTS_PER_NODE)
+        },
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{
+        save_with,
+        savedata,
+        SAVE,
+    };
+
+    fn percent(items: usize, total: i32) -> f32 {
+        items as f32 / total as f32 * 100.0
+    }
+
+    #[test]
+    fn item_limit_is_the_last_count_under_the_limit() {
+        assert_eq!(item_limit(94), 37);
+        assert_eq!(item_limit(100), 39);
+        assert_eq!(item_limit(5), 1);
+
+        for total in 1..=200 {
+            let limit = item_limit(total);
+
+            assert!(percent(limit, total) < ACHIEVEMENT_LOW_PERCENT);
+            assert!(percent(limit + 1, total) >= ACHIEVEMENT_LOW_PERCENT);
+        }
+    }
+
+    #[test]
+    fn gates_come_after_what_they_require() {
+        for (index, gate) in GATES.iter().enumerate() {
+            for id in gate.requires {
+                let position = GATES.iter().position(|gate| gate.id == *id);
+
+                assert!(
+                    position.is_some_and(|position| position < index),
+                    "{} requires {}, which isn't an earlier gate",
+                    gate.id,
+                    id,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn remaining_counts_uncollected_gates() {
+        let savedata = savedata(SAVE);
+        let requirements = LowPercent::new(&savedata).requirements();
+
+        assert_eq!(remaining(&requirements), GATES.len() - 2);
+    }
+
+    #[test]
+    fn saved_requirements_add_to_the_minimum_set() {
+        // The save says the Address Disruptor needed the Kilver.
+        let xml = save_with(&[(
+            "<mName>AddressDisruptor</mName><mType>TOOL</mType>\
+             <mConsumable>false</mConsumable>\
+             <mExcludedFromCount>false</mExcludedFromCount>",
+            "<mName>AddressDisruptor</mName><mType>TOOL</mType>\
+             <mConsumable>false</mConsumable>\
+             <mExcludedFromCount>false</mExcludedFromCount>\
+             <mRequiredItem>Kilver</mRequiredItem>",
+        )]);
+        let savedata = savedata(&xml);
+        let requirements = LowPercent::new(&savedata).requirements();
+
+        let kilver = requirements
+            .iter()
+            .find(|requirement| requirement.id == "Kilver")
+            .unwrap();
+
+        assert!(!kilver.collected);
+        assert!(kilver.type_.is_none());
+        assert_eq!(kilver.needed_by.as_deref(), Some("AddressDisruptor"));
+        assert_eq!(requirements.len(), GATES.len() + 1);
+        assert_eq!(remaining(&requirements), GATES.len() - 1);
+    }
+
+    #[test]
+    fn saved_requirements_on_gates_arent_repeated() {
+        let xml = save_with(&[(
+            "<mName>AddressDisruptor</mName><mType>TOOL</mType>\
+             <mConsumable>false</mConsumable>\
+             <mExcludedFromCount>false</mExcludedFromCount>",
+            "<mName>AddressDisruptor</mName><mType>TOOL</mType>\
+             <mConsumable>false</mConsumable>\
+             <mExcludedFromCount>false</mExcludedFromCount>\
+             <mRequiredItem>AxiomDisruptor</mRequiredItem>",
+        )]);
+        let savedata = savedata(&xml);
+        let requirements = LowPercent::new(&savedata).requirements();
+
+        assert_eq!(requirements.len(), GATES.len());
+        assert_eq!(reason(&requirements[1]), format!(
+            "{}, needs {}",
+            GATES[1].reason,
+            items::display_name("AxiomDisruptor"),
+        ));
+    }
+
+    #[test]
+    fn drops_dont_count() {
+        assert!(!type_counts(THItemType::HealthDrop));
+        assert!(!type_counts(THItemType::GlitchBombDrop));
+        assert!(type_counts(THItemType::PermanentUpgrade));
+    }
+}
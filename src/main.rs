@@ -3,96 +3,438 @@
 // This mostly assists with the Steam version of the game, since those save
 // files are encrypted.
 #![forbid(unsafe_code)]
-use anyhow::Result;
+use anyhow::{
+    anyhow,
+    Result,
+};
 use clap::ArgMatches;
 use serde_xml_rs::from_reader;
-use std::fs::OpenOptions;
 use std::io::{
     self,
     prelude::*,
 };
 
 mod achievements;
+mod areas;
+mod bestiary;
+mod bosses;
+mod category;
 mod cli;
+mod clock;
+mod creatures;
 mod crypto;
+mod document;
+mod editor;
+mod format;
+mod info;
+mod items;
+mod keypoints;
+mod lowpercent;
+mod output;
+mod pace;
+mod passwords;
+mod profiles;
+mod progress;
+mod randomizer;
 mod savedata;
+mod secretworlds;
+mod steam;
+#[cfg(test)]
+mod testdata;
+mod verify;
 
 use achievements::Achievements;
+use areas::Areas;
+use bestiary::Bestiary;
+use bosses::Bosses;
+use category::Category;
+use clock::Clock;
 use crypto::{
-    decrypt_file,
     encrypt_file,
+    open_input,
+    write_encrypted_file,
+    KeyProfile,
+    STDIO,
+};
+use document::Document;
+use info::Info;
+use lowpercent::LowPercent;
+use output::{
+    output_filename,
+    Output,
+    OutputMode,
+};
+use pace::Pace;
+use passwords::Passwords;
+use profiles::KeySelection;
+use progress::Progress;
+use randomizer::Randomizer;
+use savedata::{
+    Creature,
+    THSaveData,
 };
-use savedata::THSaveData;
+use secretworlds::SecretWorlds;
+use steam::SteamAchievements;
+use verify::Verify;
 
-fn read_savedata(filename: &str, unencrypted: bool) -> Result<THSaveData> {
+// Read the raw save data XML, along with the key profile used to decrypt it.
+fn read_save_bytes(
+    filename: &str,
+    unencrypted: bool,
+    keys: &KeySelection,
+) -> Result<(Vec<u8>, Option<KeyProfile>)> {
     let data = if unencrypted {
         // File is unencrypted, we can just read it normally.
-        let mut fh = OpenOptions::new()
-            .read(true)
-            .open(filename)?;
+        let mut fh = open_input(filename)?;
 
         let mut buffer = Vec::new();
         fh.read_to_end(&mut buffer)?;
 
-        buffer
+        (buffer, None)
     }
     else {
         // Encrypted file, probably Steam. Decrypt it.
-        decrypt_file(filename)?
+        let (buffer, profile) = keys.decrypt_file(filename)?;
+
+        (buffer, Some(profile))
     };
 
+    Ok(data)
+}
+
+fn read_savedata(
+    filename: &str,
+    unencrypted: bool,
+    keys: &KeySelection,
+) -> Result<THSaveData> {
+    let (data, _) = read_save_bytes(filename, unencrypted, keys)?;
     let savedata = from_reader(&*data)?;
 
     Ok(savedata)
 }
 
+// Apply an edit to the save game XML, writing the result to the output file.
+fn edit_savedata<F>(
+    input: &str,
+    output: &str,
+    mode: OutputMode,
+    unencrypted: bool,
+    keys: &KeySelection,
+    edit: F,
+) -> Result<()>
+where
+    F: FnOnce(&mut Document) -> Result<()>,
+{
+    let (data, profile) = read_save_bytes(input, unencrypted, keys)?;
+    let mut document = Document::parse(&data)?;
+
+    edit(&mut document)?;
+
+    let data = document.to_bytes()?;
+
+    // Make sure that the edited save data still loads before writing it.
+    let _: THSaveData = from_reader(&*data)?;
+
+    write_save_bytes(output, mode, &data, profile.as_ref())
+}
+
+// Write raw save data XML, encrypting it if a key profile is given.
+fn write_save_bytes(
+    output: &str,
+    mode: OutputMode,
+    data: &[u8],
+    profile: Option<&KeyProfile>,
+) -> Result<()> {
+    match profile {
+        Some(profile) => {
+            write_encrypted_file(output, mode, data, profile)?;
+        },
+        None => {
+            let mut fh = Output::create(output, mode)?;
+
+            fh.write_all(data)?;
+            fh.commit()?;
+        },
+    }
+
+    Ok(())
+}
+
 fn achievements(matches: &ArgMatches) -> Result<()> {
     // Required, safe to unwrap
     let filename    = matches.value_of("INPUT").unwrap();
     let unencrypted = matches.is_present("UNENCRYPTED");
-    let savedata    = read_savedata(filename, unencrypted)?;
+    let keys        = KeySelection::from_matches(matches)?;
+    let savedata    = read_savedata(filename, unencrypted, &keys)?;
+    let verbose     = matches.is_present("VERBOSE");
 
     let achievements = Achievements::new(&savedata);
-    achievements.progress();
+
+    if let Some(directory) = matches.value_of("STEAM") {
+        let user = matches.value_of("STEAM_USER");
+        let steam = SteamAchievements::load(directory, user)?;
+
+        achievements.steam_report(&steam, verbose);
+    }
+    else {
+        achievements.progress(verbose);
+    }
+
+    Ok(())
+}
+
+fn areas(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let incomplete = matches.is_present("INCOMPLETE");
+
+    // Has a default, safe to unwrap
+    let sort = matches.value_of("SORT").unwrap().parse()?;
+
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let areas = Areas::new(&savedata);
+
+    areas.report(sort, incomplete);
+
+    Ok(())
+}
+
+fn bestiary(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let remaining = matches.is_present("REMAINING");
+
+    // Has a default, safe to unwrap
+    let sort = matches.value_of("SORT").unwrap().parse()?;
+
+    let category = matches.value_of("CATEGORY")
+        .map(str::parse)
+        .transpose()?;
+
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let bestiary = Bestiary::new(&savedata);
+
+    bestiary.report(sort, category, remaining);
+
+    Ok(())
+}
+
+fn bosses(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let bosses = Bosses::new(&savedata);
+
+    bosses.report();
+
+    Ok(())
+}
+
+fn category_validate(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let name = matches.value_of("CATEGORY").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+
+    // Has a default, safe to unwrap
+    let rules = matches.value_of("RULES").unwrap();
+
+    let category = Category::load(rules, name)?;
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+
+    let num = category.validate(&savedata)?;
+    if num > 0 {
+        let word = if num > 1 {
+            "rules"
+        }
+        else {
+            "rule"
+        };
+
+        return Err(anyhow!("{} {} failed for {}", num, word, filename));
+    }
+
+    Ok(())
+}
+
+fn category(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        // Check a save against every rule in a category
+        ("validate", Some(matches)) => {
+            category_validate(matches)?
+        },
+
+        // Unreachable
+        (_, _) => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn check(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+
+    // Required, safe to unwrap
+    let required = matches.values_of("REQUIRE")
+        .unwrap()
+        .map(achievements::find)
+        .collect::<Result<Vec<_>>>()?;
+
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let achievements = Achievements::new(&savedata);
+
+    let num = achievements.check(&required);
+    if num > 0 {
+        let word = if num > 1 {
+            "conditions"
+        }
+        else {
+            "condition"
+        };
+
+        return Err(anyhow!("{} {} failed for {}", num, word, filename));
+    }
+
+    Ok(())
+}
+
+// Glitch or unglitch the given creatures, writing out the edited save.
+fn creatures_edit(
+    matches: &ArgMatches,
+    edit: fn(&mut Document, &Creature) -> Result<()>,
+) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+
+    // Work out which creatures were meant before touching the save, so that
+    // typos are caught early.
+    let creatures = matches.values_of("CREATURE")
+        .unwrap()
+        .map(creatures::resolve)
+        .collect::<Result<Vec<Creature>>>()?;
+
+    let in_place = matches.is_present("IN_PLACE");
+    let mode = OutputMode::new(in_place || matches.is_present("FORCE"));
+    let output = matches.value_of("OUTPUT");
+    let output = output_filename(filename, output, in_place)?
+        .ok_or_else(|| anyhow!("Editing requires --output or --in-place"))?;
+
+    let keys = KeySelection::from_matches(matches)?;
+
+    edit_savedata(filename, output, mode, unencrypted, &keys, |document| {
+        for creature in &creatures {
+            edit(document, creature)?;
+        }
+
+        Ok(())
+    })
+}
+
+fn creatures(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        // Mark creatures as glitched
+        ("glitch", Some(matches)) => {
+            creatures_edit(matches, creatures::glitch)?
+        },
+
+        // Mark creatures as not glitched
+        ("unglitch", Some(matches)) => {
+            creatures_edit(matches, creatures::unglitch)?
+        },
+
+        // Unreachable
+        (_, _) => unreachable!(),
+    }
 
     Ok(())
 }
 
 fn decrypt(matches: &ArgMatches) -> Result<()> {
     let filename = matches.value_of("INPUT").unwrap();
+    let in_place = matches.is_present("IN_PLACE");
+    let mode = OutputMode::new(in_place || matches.is_present("FORCE"));
 
     // If we got an output argument, we write to that file, otherwise write
     // to STDOUT
     let output = matches.value_of("OUTPUT");
-    let mut output: Box<dyn io::Write> = if let Some(output) = output {
-        // Write to a new file. Error out if the file already exists to avoid
-        // accidentally overwriting things.
-        let fh = OpenOptions::new()
-            .create_new(true)
-            .write(true)
-            .open(output)?;
+    let output = output_filename(filename, output, in_place)?
+        .unwrap_or(STDIO);
+    let mut output = Output::create(output, mode)?;
+
+    // Decrypt the data, streaming it to the output unless it needs to be
+    // formatted first.
+    let keys = KeySelection::from_matches(matches)?;
+    let (mut reader, _) = keys.decrypt_reader(filename)?;
+
+    if matches.is_present("PRETTY") {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        let mut document = Document::parse(&data)?;
+        format::format(&mut document, matches.is_present("SORT"));
 
-        Box::new(fh)
+        output.write_all(&document.to_bytes()?)?;
     }
     else {
-        let stdout = io::stdout();
-
-        Box::new(stdout)
-    };
+        io::copy(&mut reader, &mut output)?;
+    }
 
-    // Decrypt the data and write it to the output
-    let data = decrypt_file(filename)?;
-    output.write_all(&data)?;
+    output.commit()?;
 
     Ok(())
 }
 
 fn encrypt(matches: &ArgMatches) -> Result<()> {
-    // Both are required and safe to unwrap.
+    // Required and safe to unwrap.
+    let input = matches.value_of("INPUT").unwrap();
+    let in_place = matches.is_present("IN_PLACE");
+    let mode = OutputMode::new(in_place || matches.is_present("FORCE"));
+
+    // Required unless IN_PLACE, safe to unwrap
+    let output = matches.value_of("OUTPUT");
+    let output = output_filename(input, output, in_place)?
+        .unwrap();
+
+    let keys = KeySelection::from_matches(matches)?;
+    let profile = keys.encryption_profile()?;
+
+    encrypt_file(input, output, mode, profile)?;
+
+    Ok(())
+}
+
+fn fmt(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
     let input = matches.value_of("INPUT").unwrap();
-    let output = matches.value_of("OUTPUT").unwrap();
+    let sort = matches.is_present("SORT");
+    let in_place = matches.is_present("IN_PLACE");
+    let mode = OutputMode::new(in_place || matches.is_present("FORCE"));
+
+    // Write to STDOUT if no output was given
+    let output = matches.value_of("OUTPUT");
+    let output = output_filename(input, output, in_place)?
+        .unwrap_or(STDIO);
 
-    encrypt_file(input, output)?;
+    let mut data = Vec::new();
+    open_input(input)?.read_to_end(&mut data)?;
+
+    let mut document = Document::parse(&data)?;
+    format::format(&mut document, sort);
+
+    let mut output = Output::create(output, mode)?;
+    output.write_all(&document.to_bytes()?)?;
+    output.commit()?;
 
     Ok(())
 }
@@ -101,27 +443,254 @@ fn hacker(matches: &ArgMatches) -> Result<()> {
     // Required, safe to unwrap
     let filename = matches.value_of("INPUT").unwrap();
     let unencrypted = matches.is_present("UNENCRYPTED");
-    let savedata = read_savedata(filename, unencrypted)?;
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
     let achievements = Achievements::new(&savedata);
 
-    if let Some(remaining) = achievements.hacker_requires() {
-        let num = remaining.len();
-        let word = if num > 1 {
-            "creatures"
-        }
-        else {
-            "creature"
-        };
+    achievements.hacker_report();
+
+    Ok(())
+}
+
+fn info(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let info = Info::new(&savedata);
+
+    info.report();
+
+    Ok(())
+}
+
+fn lowpercent_plan(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let lowpercent = LowPercent::new(&savedata);
 
-        println!("Hacker Achievement requires {} more {}:", num, word);
+    lowpercent.plan();
 
-        for creature in remaining {
-            println!("  - {} ({:?})", creature, creature);
+    Ok(())
+}
+
+fn lowpercent(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        // Required items and headroom under the Low % limit
+        ("plan", Some(matches)) => {
+            lowpercent_plan(matches)?
+        },
+
+        // Unreachable
+        (_, _) => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn open(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let keys = KeySelection::from_matches(matches)?;
+
+    // The save is written back over itself, so it must be a real file.
+    let output = output_filename(filename, None, true)?.unwrap();
+
+    let (data, profile) = read_save_bytes(filename, unencrypted, &keys)?;
+    let mut document = Document::parse(&data)?;
+    document.indent();
+
+    let edited = editor::edit(&document.to_bytes()?, |data| {
+        let _: THSaveData = from_reader(data)?;
+
+        Ok(())
+    })?;
+
+    // Re-encrypt with the same profile that the save was decrypted with.
+    let mode = OutputMode::Replace;
+
+    match edited {
+        Some(data) => write_save_bytes(output, mode, &data, profile.as_ref())?,
+        None => {
+            eprintln!("No changes made to {}", filename);
+        },
+    }
+
+    Ok(())
+}
+
+fn pace(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+
+    // Has a default, safe to unwrap
+    let target = matches.value_of("TARGET").unwrap().parse()?;
+
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+
+    // Defaults to the clock the in-game timer uses.
+    let clock = match matches.value_of("CLOCK") {
+        Some(clock) => clock.parse()?,
+        None        => Clock::for_savedata(&savedata),
+    };
+
+    let pace = Pace::new(&savedata);
+
+    pace.report(target, clock);
+
+    Ok(())
+}
+
+fn passwords(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let add: Vec<&str> = matches.values_of("ADD")
+        .map(|values| values.collect())
+        .unwrap_or_default();
+    let remove: Vec<&str> = matches.values_of("REMOVE")
+        .map(|values| values.collect())
+        .unwrap_or_default();
+
+    let keys = KeySelection::from_matches(matches)?;
+
+    if add.is_empty() && remove.is_empty() {
+        let savedata = read_savedata(filename, unencrypted, &keys)?;
+        let passwords = Passwords::new(&savedata);
+
+        passwords.list();
+
+        return Ok(());
+    }
+
+    let in_place = matches.is_present("IN_PLACE");
+    let mode = OutputMode::new(in_place || matches.is_present("FORCE"));
+    let output = matches.value_of("OUTPUT");
+    let output = output_filename(filename, output, in_place)?
+        .ok_or_else(|| anyhow!("Editing requires --output or --in-place"))?;
+
+    edit_savedata(filename, output, mode, unencrypted, &keys, |document| {
+        for password in remove {
+            passwords::remove(document, password)?;
+        }
+
+        for password in add {
+            passwords::add(document, password)?;
         }
+
+        Ok(())
+    })
+}
+
+fn progress(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let progress = Progress::new(&savedata);
+
+    progress.report();
+
+    Ok(())
+}
+
+fn randomizer_spoiler(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let remaining = matches.is_present("REMAINING");
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let randomizer = Randomizer::new(&savedata)?;
+
+    randomizer.spoiler(remaining);
+
+    Ok(())
+}
+
+fn randomizer_track(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let randomizer = Randomizer::new(&savedata)?;
+
+    randomizer.track();
+
+    Ok(())
+}
+
+fn randomizer(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        // Full spoiler log for the seed
+        ("spoiler", Some(matches)) => {
+            randomizer_spoiler(matches)?
+        },
+
+        // Spoiler free progress through the seed
+        ("track", Some(matches)) => {
+            randomizer_track(matches)?
+        },
+
+        // Unreachable
+        (_, _) => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn secretworlds(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let keys = KeySelection::from_matches(matches)?;
+    let savedata = read_savedata(filename, unencrypted, &keys)?;
+    let secretworlds = SecretWorlds::new(&savedata);
+
+    secretworlds.report();
+
+    Ok(())
+}
+
+fn verify(matches: &ArgMatches) -> Result<()> {
+    // Required, safe to unwrap
+    let filename = matches.value_of("INPUT").unwrap();
+    let unencrypted = matches.is_present("UNENCRYPTED");
+    let keys = KeySelection::from_matches(matches)?;
+
+    // Verification looks at the raw data, so read it ourselves.
+    let mut data = Vec::new();
+    open_input(filename)?.read_to_end(&mut data)?;
+
+    let keys = if unencrypted {
+        None
     }
     else {
-        println!("Hacker Achievement requires:");
-        println!("  - All creatures required");
+        Some(&keys)
+    };
+
+    let verify = Verify::new(&data, keys);
+    verify.report(filename);
+
+    let num = verify.problems().len();
+    if num > 0 {
+        let word = if num > 1 {
+            "problems"
+        }
+        else {
+            "problem"
+        };
+
+        return Err(anyhow!("{} {} found in {}", num, word, filename));
     }
 
     Ok(())
@@ -137,6 +706,36 @@ fn main() -> Result<()> {
             achievements(matches)?
         },
 
+        // Display per area progress
+        ("areas", Some(matches)) => {
+            areas(matches)?
+        },
+
+        // Display bestiary completion
+        ("bestiary", Some(matches)) => {
+            bestiary(matches)?
+        },
+
+        // Boss kills and the evidence for them
+        ("bosses", Some(matches)) => {
+            bosses(matches)?
+        },
+
+        // Speedrun category rules
+        ("category", Some(matches)) => {
+            category(matches)?
+        },
+
+        // Scripted achievement condition checks
+        ("check", Some(matches)) => {
+            check(matches)?
+        },
+
+        // Edit glitched creatures
+        ("creatures", Some(matches)) => {
+            creatures(matches)?
+        },
+
         // Simply decrypt the given file
         ("decrypt", Some(matches)) => {
             decrypt(matches)?
@@ -147,10 +746,60 @@ fn main() -> Result<()> {
             encrypt(matches)?
         },
 
+        // Format decrypted save data
+        ("fmt", Some(matches)) => {
+            fmt(matches)?
+        },
+
         // View details for Hacker achievement
         ("hacker", Some(matches)) => {
             hacker(matches)?
-        }
+        },
+
+        // Save game summary
+        ("info", Some(matches)) => {
+            info(matches)?
+        },
+
+        // Low % run planning
+        ("lowpercent", Some(matches)) => {
+            lowpercent(matches)?
+        },
+
+        // Edit a save game in $EDITOR
+        ("open", Some(matches)) => {
+            open(matches)?
+        },
+
+        // Projected finishing time
+        ("pace", Some(matches)) => {
+            pace(matches)?
+        },
+
+        // View or edit entered passwords
+        ("passwords", Some(matches)) => {
+            passwords(matches)?
+        },
+
+        // Main story checklist
+        ("progress", Some(matches)) => {
+            progress(matches)?
+        },
+
+        // Randomizer seed details
+        ("randomizer", Some(matches)) => {
+            randomizer(matches)?
+        },
+
+        // Secret world report
+        ("secretworlds", Some(matches)) => {
+            secretworlds(matches)?
+        },
+
+        // Check that a save game will load
+        ("verify", Some(matches)) => {
+            verify(matches)?
+        },
 
         // Unreachable
         (_, _) => unreachable!(),
@@ -158,3 +807,63 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::save_with;
+    use tempfile::NamedTempFile;
+
+    fn save_file(xml: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(xml.as_bytes()).unwrap();
+
+        file
+    }
+
+    // Runs the check subcommand against an unencrypted save.
+    fn run_check(xml: &str, required: &[&str]) -> Result<()> {
+        let file = save_file(xml);
+        let filename = file.path().to_str().unwrap();
+
+        let mut args = vec!["avsg", "check", "--unencrypted"];
+        for id in required {
+            args.push("--require");
+            args.push(id);
+        }
+        args.push(filename);
+
+        let args = cli::parse_args_from(&args);
+        let (_, matches) = args.subcommand();
+
+        check(matches.unwrap())
+    }
+
+    #[test]
+    fn check_passes_when_conditions_hold() {
+        let save = save_with(&[]);
+        let required = ["low_percent", "mostly_invincible"];
+
+        assert!(run_check(&save, &required).is_ok());
+    }
+
+    #[test]
+    fn check_fails_with_the_number_of_failed_conditions() {
+        let save = save_with(&[]);
+        let error = run_check(&save, &["invincible", "hacker", "low_percent"])
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.starts_with("2 conditions failed for "), "{}", error);
+    }
+
+    #[test]
+    fn check_rejects_unknown_achievements() {
+        let save = save_with(&[]);
+        let error = run_check(&save, &["low_percent", "invincable"])
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.starts_with("Unknown achievement: invincable"));
+    }
+}
@@ -0,0 +1,367 @@
+// output: Write output files without ever leaving a partial file behind.
+//
+// Files are always written to a temporary file in the same directory, synced
+// to disk, and then renamed into place. Existing files are backed up before
+// they're replaced. Output to STDOUT is held in an anonymous temporary file
+// until it's complete, so that errors part way through don't print anything.
+use crate::crypto::STDIO;
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use std::fs::{
+    self,
+    File,
+};
+use std::io::{
+    self,
+    prelude::*,
+    SeekFrom,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::str;
+use tempfile::NamedTempFile;
+
+// Extension added to the original file when it's backed up.
+const BACKUP_EXTENSION: &str = "bak";
+
+// How to treat an output file that already exists.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputMode {
+    // Error out if the file exists, to avoid accidentally overwriting things.
+    CreateNew,
+
+    // Atomically replace the file, keeping a backup of the original.
+    Replace,
+}
+
+impl OutputMode {
+    pub fn new(force: bool) -> Self {
+        if force {
+            Self::Replace
+        }
+        else {
+            Self::CreateNew
+        }
+    }
+}
+
+pub enum Output {
+    Stdout(File),
+    File(NamedTempFile, PathBuf, OutputMode),
+}
+
+impl Output {
+    // Create an output file, with "-" meaning STDOUT.
+    pub fn create(filename: &str, mode: OutputMode) -> Result<Self> {
+        if filename == STDIO {
+            let spool = tempfile::tempfile()
+                .context("Couldn't create temporary file for STDOUT")?;
+
+            return Ok(Self::Stdout(spool));
+        }
+
+        let path = Path::new(filename);
+
+        // Checked again when committing, but fail early rather than after
+        // all the work is done.
+        if mode == OutputMode::CreateNew && path.exists() {
+            return Err(anyhow!(
+                "Couldn't create {}, it already exists",
+                filename,
+            ));
+        }
+
+        // The temporary file must be on the same filesystem as the target
+        // for the rename to be atomic, so create it alongside the target.
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _                                        => Path::new("."),
+        };
+
+        let mut builder = tempfile::Builder::new();
+        builder.prefix(".avsg");
+
+        if let Some(permissions) = permissions(path) {
+            builder.permissions(permissions);
+        }
+
+        let temp = builder
+            .tempfile_in(dir)
+            .with_context(|| {
+                format!("Couldn't create temporary file in {}", dir.display())
+            })?;
+
+        Ok(Self::File(temp, path.to_path_buf(), mode))
+    }
+
+    // Finish writing the output. Files only appear once this is called, if
+    // it isn't the target is left untouched.
+    pub fn commit(self) -> Result<()> {
+        let (temp, path, mode) = match self {
+            Self::Stdout(mut spool) => {
+                let mut stdout = io::stdout();
+
+                spool.seek(SeekFrom::Start(0))?;
+                io::copy(&mut spool, &mut stdout)?;
+                stdout.flush()?;
+
+                return Ok(());
+            },
+            Self::File(temp, path, mode) => (temp, path, mode),
+        };
+
+        temp.as_file().sync_all()?;
+
+        let backup = if mode == OutputMode::Replace && path.exists() {
+            Some(backup(&path)?)
+        }
+        else {
+            None
+        };
+
+        match mode {
+            OutputMode::CreateNew => {
+                temp.persist_noclobber(&path).with_context(|| {
+                    format!("Couldn't create {}", path.display())
+                })?;
+            },
+            OutputMode::Replace => {
+                temp.persist(&path).with_context(|| {
+                    format!("Couldn't replace {}", path.display())
+                })?;
+            },
+        }
+
+        sync_parent(&path)?;
+
+        if let Some(backup) = backup {
+            eprintln!("Backed up original to {}", backup.display());
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(spool)   => spool.write(buf),
+            Self::File(fh, _, _)  => fh.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(spool)   => spool.flush(),
+            Self::File(fh, _, _)  => fh.flush(),
+        }
+    }
+}
+
+// Permissions for the temporary file. Replaced files keep their permissions,
+// new files get the usual ones rather than the private ones tempfile uses.
+fn permissions(path: &Path) -> Option<fs::Permissions> {
+    match fs::metadata(path) {
+        Ok(metadata) => Some(metadata.permissions()),
+        Err(_)       => default_permissions(),
+    }
+}
+
+#[cfg(unix)]
+fn default_permissions() -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Restricted by the umask, as with any other new file.
+    Some(fs::Permissions::from_mode(0o666))
+}
+
+#[cfg(not(unix))]
+fn default_permissions() -> Option<fs::Permissions> {
+    None
+}
+
+// Back up the file at path, replacing any previous backup. A hard link is
+// used where possible, so the original is never missing, even briefly.
+fn backup(path: &Path) -> Result<PathBuf> {
+    let mut extension = path
+        .extension()
+        .map(|ext| ext.to_os_string())
+        .unwrap_or_default();
+
+    if !extension.is_empty() {
+        extension.push(".");
+    }
+    extension.push(BACKUP_EXTENSION);
+
+    let backup = path.with_extension(extension);
+
+    if backup.exists() {
+        fs::remove_file(&backup)?;
+    }
+
+    if fs::hard_link(path, &backup).is_err() {
+        fs::copy(path, &backup)?;
+        File::open(&backup)?.sync_all()?;
+    }
+
+    Ok(backup)
+}
+
+// Sync the directory containing path, so that the rename is on disk.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _                                        => Path::new("."),
+    };
+
+    File::open(dir)?.sync_all()?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+// Work out the output filename. When editing in place this is the input,
+// which must be a real file.
+pub fn output_filename<'a>(
+    input: &'a str,
+    output: Option<&'a str>,
+    in_place: bool,
+) -> Result<Option<&'a str>> {
+    if !in_place {
+        return Ok(output);
+    }
+
+    if input == STDIO {
+        return Err(anyhow!("STDIN can't be edited in place"));
+    }
+
+    Ok(Some(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create(path: &Path, mode: OutputMode) -> Result<Output> {
+        Output::create(path.to_str().unwrap(), mode)
+    }
+
+    fn write_output(path: &Path, mode: OutputMode, data: &[u8]) -> Result<()> {
+        let mut output = create(path, mode)?;
+        output.write_all(data)?;
+        output.commit()
+    }
+
+    // Names of the files in a directory, to check for stray temporary files.
+    fn files(dir: &TempDir) -> Vec<String> {
+        let mut files: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn creates_new_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Save0.sav");
+
+        write_output(&path, OutputMode::CreateNew, b"new").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(files(&dir), ["Save0.sav"]);
+    }
+
+    #[test]
+    fn create_new_refuses_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Save0.sav");
+        fs::write(&path, b"original").unwrap();
+
+        let result = write_output(&path, OutputMode::CreateNew, b"new");
+
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"original");
+        assert_eq!(files(&dir), ["Save0.sav"]);
+    }
+
+    #[test]
+    fn create_new_refuses_file_created_while_writing() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Save0.sav");
+
+        let mut output = create(&path, OutputMode::CreateNew).unwrap();
+        output.write_all(b"new").unwrap();
+        fs::write(&path, b"original").unwrap();
+
+        assert!(output.commit().is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"original");
+        assert_eq!(files(&dir), ["Save0.sav"]);
+    }
+
+    #[test]
+    fn replace_keeps_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Save0.sav");
+        fs::write(&path, b"original").unwrap();
+
+        write_output(&path, OutputMode::Replace, b"new").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(files(&dir), ["Save0.sav", "Save0.sav.bak"]);
+        assert_eq!(
+            fs::read(dir.path().join("Save0.sav.bak")).unwrap(),
+            b"original",
+        );
+    }
+
+    #[test]
+    fn replace_creates_missing_file_without_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Save0.sav");
+
+        write_output(&path, OutputMode::Replace, b"new").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(files(&dir), ["Save0.sav"]);
+    }
+
+    #[test]
+    fn uncommitted_new_file_never_appears() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Save0.sav");
+
+        let mut output = create(&path, OutputMode::CreateNew).unwrap();
+        output.write_all(b"partial").unwrap();
+        drop(output);
+
+        assert!(files(&dir).is_empty());
+    }
+
+    #[test]
+    fn uncommitted_replace_leaves_original() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Save0.sav");
+        fs::write(&path, b"original").unwrap();
+
+        let mut output = create(&path, OutputMode::Replace).unwrap();
+        output.write_all(b"partial").unwrap();
+        drop(output);
+
+        assert_eq!(fs::read(&path).unwrap(), b"original");
+        assert_eq!(files(&dir), ["Save0.sav"]);
+    }
+}
@@ -0,0 +1,442 @@
+// pace: Project the finishing time of a run from the checkpoints reached.
+use crate::achievements::{
+    OVERCLOCKED_MAX_FRAMES,
+    SPEEDRUNNER_MAX_FRAMES,
+};
+use crate::bosses::Bosses;
+use crate::clock::{
+    frames_to_duration,
+    Clock,
+};
+use crate::savedata::THSaveData;
+use anyhow::{
+    anyhow,
+    Error,
+    Result,
+};
+use std::str;
+
+// Bosses in the order they're usually defeated, with the fraction of the
+// total time a typical run has used by the time each one is defeated. Athetos
+// is the end of the game, and is never in the save data.
+const SPLITS: &[(&str, f64)] = &[
+    ("Xedur",    0.07),
+    ("Telal",    0.20),
+    ("Uruku",    0.33),
+    ("Gir-Tab",  0.47),
+    ("Vision",   0.60),
+    ("Clone",    0.70),
+    ("Ukhu",     0.80),
+    ("Sentinel", 0.91),
+    ("Athetos",  1.00),
+];
+
+// The time limit to plan against.
+#[derive(Clone, Copy, Debug)]
+pub enum PaceTarget {
+    // Under 4 hours.
+    Overclocked,
+
+    // Under 2 hours.
+    Speedrunner,
+}
+
+impl PaceTarget {
+    fn max_frames(self) -> f64 {
+        match self {
+            Self::Overclocked => OVERCLOCKED_MAX_FRAMES,
+            Self::Speedrunner => SPEEDRUNNER_MAX_FRAMES,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Overclocked => "Overclocked",
+            Self::Speedrunner => "Speedrunner",
+        }
+    }
+}
+
+impl str::FromStr for PaceTarget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "overclocked" => Ok(Self::Overclocked),
+            "speedrunner" => Ok(Self::Speedrunner),
+            _             => Err(anyhow!("Unknown pace target: {}", s)),
+        }
+    }
+}
+
+// How a split was reached.
+#[derive(Clone, Copy, Debug)]
+enum Split {
+    // Recorded as a speedrun checkpoint, at the given frame.
+    Checkpoint(f64),
+
+    // Recorded only as a key point, so we don't know when.
+    KeyPoint,
+
+    // Not reached yet.
+    NotReached,
+}
+
+// Projects the finishing time from the furthest split with a recorded time,
+// as the time played so far plus the rest of the run at the pace of the
+// splits so far. Splits without a time can't say anything about the pace.
+fn projected_finish(splits: &[(&str, f64, Split)], now: f64) -> Option<f64> {
+    splits
+        .iter()
+        .rev()
+        .find_map(|&(_, fraction, split)| {
+            match split {
+                Split::Checkpoint(frames) => Some((fraction, frames)),
+                _                         => None,
+            }
+        })
+        .map(|(fraction, frames)| now + frames / fraction * (1.0 - fraction))
+}
+
+// Describes the difference between a time and its budget.
+fn versus_budget(frames: f64, budget: f64) -> String {
+    if frames > budget {
+        format!("{} behind", frames_to_duration(frames - budget))
+    }
+    else {
+        format!("{} ahead", frames_to_duration(budget - frames))
+    }
+}
+
+pub struct Pace<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> Pace<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    fn split(&self, boss: &str) -> Split {
+        let checkpoint = self.savedata.speedrun_checkpoints
+            .iter()
+            .flatten()
+            .find(|checkpoint| checkpoint.name == boss);
+
+        if let Some(checkpoint) = checkpoint {
+            return Split::Checkpoint(checkpoint.frames as f64);
+        }
+
+        // Sequence breaks can skip bosses, so only count bosses that the game
+        // recorded as defeated.
+        if Bosses::new(self.savedata).state(boss).is_certain() {
+            Split::KeyPoint
+        }
+        else {
+            Split::NotReached
+        }
+    }
+
+    // Checkpoints are recorded with the save's in-game timer. Returns the
+    // factor that converts them to the given clock, estimated from how far
+    // apart the two clocks are now.
+    fn checkpoint_scale(&self, clock: Clock) -> f64 {
+        let recorded = Clock::for_savedata(self.savedata).frames(self.savedata);
+
+        if recorded > 0.0 {
+            clock.frames(self.savedata) / recorded
+        }
+        else {
+            1.0
+        }
+    }
+
+    // Prints the projected finishing time for the target on the given clock,
+    // and a table of how each split compares to its share of the time limit.
+    pub fn report(&self, target: PaceTarget, clock: Clock) {
+        let max_frames = target.max_frames();
+        let now = clock.frames(self.savedata);
+        let other = clock.other();
+        let recorded = Clock::for_savedata(self.savedata);
+        let scale = self.checkpoint_scale(clock);
+
+        // Every time below is on the given clock, so that checkpoints can be
+        // compared with the time played.
+        let splits: Vec<(&str, f64, Split)> = SPLITS
+            .iter()
+            .map(|&(boss, fraction)| {
+                let split = match self.split(boss) {
+                    Split::Checkpoint(frames) => {
+                        Split::Checkpoint(frames * scale)
+                    },
+                    split => split,
+                };
+
+                (boss, fraction, split)
+            })
+            .collect();
+
+        // The furthest split reached, with or without a recorded time.
+        let latest_fraction = splits
+            .iter()
+            .rev()
+            .find(|(_, _, split)| !matches!(split, Split::NotReached))
+            .map(|&(_, fraction, _)| fraction);
+
+        println!(
+            "{} Pace ({}): {} played ({} {}), limit {}",
+            target.name(),
+            clock,
+            frames_to_duration(now),
+            frames_to_duration(other.frames(self.savedata)),
+            other,
+            frames_to_duration(max_frames),
+        );
+
+        match (projected_finish(&splits, now), latest_fraction) {
+            (Some(projected), _) => {
+                let verdict = if projected < max_frames {
+                    "On pace"
+                }
+                else {
+                    "Behind pace"
+                };
+
+                println!(
+                    "Projected finish: {} ({})",
+                    frames_to_duration(projected),
+                    verdict,
+                );
+            },
+            (None, Some(_)) => {
+                println!(
+                    "Projected finish: Unknown, no boss times recorded yet",
+                );
+            },
+            (None, None) => {
+                println!("Projected finish: Unknown, no bosses defeated yet");
+            },
+        }
+
+        let latest_fraction = latest_fraction.unwrap_or(0.0);
+        let remaining_frames = max_frames - now;
+        let remaining_fraction = 1.0 - latest_fraction;
+
+        // Running total of the times to reach each split by, and the split
+        // that the next segment starts from.
+        let mut target_frames = now;
+        let mut previous_fraction = latest_fraction;
+        let mut remaining_segments = 0;
+
+        if clock == recorded {
+            println!("Splits, checkpoints in {}:", clock);
+        }
+        else {
+            println!(
+                "Splits, checkpoints scaled from {} to {}:",
+                recorded,
+                clock,
+            );
+        }
+
+        for (boss, fraction, split) in splits {
+            let budget = max_frames * fraction;
+
+            match split {
+                Split::Checkpoint(frames) => {
+                    println!(
+                        "  - {}: {}, budget {}, {}",
+                        boss,
+                        frames_to_duration(frames),
+                        frames_to_duration(budget),
+                        versus_budget(frames, budget),
+                    );
+                },
+                Split::KeyPoint => {
+                    println!(
+                        "  - {}: Defeated, time not recorded, budget {}",
+                        boss,
+                        frames_to_duration(budget),
+                    );
+                },
+                Split::NotReached if fraction <= latest_fraction => {
+                    println!("  - {}: Skipped", boss);
+                },
+                Split::NotReached if remaining_frames <= 0.0 => {
+                    println!("  - {}: No time left", boss);
+                },
+                Split::NotReached => {
+                    // Share the time left between the remaining segments, in
+                    // proportion to a typical run.
+                    let share = (fraction - previous_fraction)
+                        / remaining_fraction;
+                    let segment = remaining_frames * share;
+
+                    target_frames += segment;
+                    previous_fraction = fraction;
+                    remaining_segments += 1;
+
+                    println!(
+                        "  - {}: {} for this segment, reach by {}",
+                        boss,
+                        frames_to_duration(segment),
+                        frames_to_duration(target_frames),
+                    );
+                },
+            }
+        }
+
+        if remaining_frames > 0.0 {
+            println!(
+                "Time left: {} for {} segments",
+                frames_to_duration(remaining_frames),
+                remaining_segments,
+            );
+        }
+        else {
+            println!(
+                "Time left: None, {} over the limit",
+                frames_to_duration(-remaining_frames),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{
+        save_with,
+        savedata,
+    };
+
+    #[test]
+    fn splits_from_checkpoints() {
+        let xml = save_with(&[(
+            "<CreatureGlitched>",
+            "<SpeedrunCheckpoint><mName>Xedur</mName><mFrames>100000</mFrames>\
+             </SpeedrunCheckpoint>\n  <CreatureGlitched>",
+        )]);
+        let savedata = savedata(&xml);
+        let pace = Pace::new(&savedata);
+
+        assert!(matches!(pace.split("Xedur"), Split::Checkpoint(frames)
+            if frames == 100000.0));
+    }
+
+    #[test]
+    fn scales_checkpoints_to_the_clock() {
+        let game = savedata(&save_with(&[]));
+        let pace = Pace::new(&game);
+
+        assert_eq!(pace.checkpoint_scale(Clock::Game), 1.0);
+        assert_eq!(pace.checkpoint_scale(Clock::Real), 432000.0 / 400000.0);
+
+        let real = savedata(&save_with(&[(
+            "<mUseRealTimers>false",
+            "<mUseRealTimers>true",
+        )]));
+        let pace = Pace::new(&real);
+
+        assert_eq!(pace.checkpoint_scale(Clock::Real), 1.0);
+        assert_eq!(pace.checkpoint_scale(Clock::Game), 400000.0 / 432000.0);
+    }
+
+    #[test]
+    fn doesnt_scale_before_any_time_is_played() {
+        let xml = save_with(&[
+            ("<mTotalFrames>432000", "<mTotalFrames>0"),
+            ("<mEffectiveFrames>400000", "<mEffectiveFrames>0"),
+        ]);
+        let savedata = savedata(&xml);
+        let pace = Pace::new(&savedata);
+
+        assert_eq!(pace.checkpoint_scale(Clock::Real), 1.0);
+    }
+
+    #[test]
+    fn splits_from_key_point_aliases() {
+        let xml = save_with(&[(
+            "<KeyPoint>XedurDefeated</KeyPoint>",
+            "<KeyPoint>XedurDefeated</KeyPoint>\n  \
+             <KeyPoint>GirTabDefeated</KeyPoint>",
+        )]);
+        let savedata = savedata(&xml);
+        let pace = Pace::new(&savedata);
+
+        assert!(matches!(pace.split("Xedur"), Split::KeyPoint));
+        assert!(matches!(pace.split("Gir-Tab"), Split::KeyPoint));
+        assert!(matches!(pace.split("Telal"), Split::NotReached));
+    }
+
+    #[test]
+    fn later_progress_doesnt_reach_a_split() {
+        // Telal is only likely to be dead, from the later key point.
+        let xml = save_with(&[(
+            "<KeyPoint>XedurDefeated</KeyPoint>",
+            "<KeyPoint>XedurDefeated</KeyPoint>\n  \
+             <KeyPoint>UrukuDefeated</KeyPoint>",
+        )]);
+        let savedata = savedata(&xml);
+        let pace = Pace::new(&savedata);
+
+        assert!(matches!(pace.split("Telal"), Split::NotReached));
+        assert!(matches!(pace.split("Athetos"), Split::NotReached));
+    }
+
+    #[test]
+    fn projects_from_checkpoints() {
+        // Xedur at 7% of the run took 100000 frames, so the remaining 93%
+        // should take 100000 / 0.07 * 0.93 frames.
+        let splits = [
+            ("Xedur", 0.07, Split::Checkpoint(100000.0)),
+            ("Telal", 0.20, Split::NotReached),
+        ];
+        let projected = projected_finish(&splits, 150000.0).unwrap();
+
+        assert!((projected - (150000.0 + 100000.0 / 0.07 * 0.93)).abs() < 1.0);
+    }
+
+    #[test]
+    fn projects_from_the_latest_checkpoint() {
+        let splits = [
+            ("Xedur", 0.07, Split::Checkpoint(100000.0)),
+            ("Telal", 0.20, Split::Checkpoint(200000.0)),
+            ("Uruku", 0.33, Split::KeyPoint),
+        ];
+        let projected = projected_finish(&splits, 250000.0).unwrap();
+
+        assert!((projected - (250000.0 + 200000.0 / 0.2 * 0.8)).abs() < 1.0);
+    }
+
+    #[test]
+    fn doesnt_project_without_checkpoints() {
+        let splits = [
+            ("Xedur", 0.07, Split::KeyPoint),
+            ("Telal", 0.20, Split::NotReached),
+        ];
+
+        assert!(projected_finish(&splits, 400000.0).is_none());
+        assert!(projected_finish(&[], 400000.0).is_none());
+    }
+
+    #[test]
+    fn splits_are_in_order() {
+        for pair in SPLITS.windows(2) {
+            assert!(pair[0].1 < pair[1].1);
+        }
+
+        assert_eq!(SPLITS.last().map(|split| split.1), Some(1.0));
+    }
+
+    #[test]
+    fn parses_targets() {
+        assert!(matches!(
+            "overclocked".parse::<PaceTarget>(),
+            Ok(PaceTarget::Overclocked),
+        ));
+        assert!("fast".parse::<PaceTarget>().is_err());
+    }
+}
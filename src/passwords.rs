@@ -0,0 +1,364 @@
+// passwords: Decode the passwords entered on the password screen.
+use crate::document::{
+    Document,
+    Element,
+};
+use crate::savedata::THSaveData;
+use anyhow::{
+    anyhow,
+    Result,
+};
+use std::str;
+
+// Elements that follow the PasswordEntry elements in the save data.
+const PASSWORD_ENTRY_FOLLOWS: &[&str] = &[
+    "AreaSaveData",
+    "SecretWorldSaveData",
+    "AutoMap",
+    "SpeedrunCheckpoint",
+    "CreatureGlitched",
+];
+
+// Save data flags toggled by passwords.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PasswordFlag {
+    BiofluxVisions,
+    JustinBailey,
+    TranslatePrimordial,
+    TranslateVykhya,
+}
+
+impl PasswordFlag {
+    // All flags, in the order they appear in the save data.
+    const ALL: &'static [Self] = &[
+        Self::BiofluxVisions,
+        Self::TranslatePrimordial,
+        Self::TranslateVykhya,
+        Self::JustinBailey,
+    ];
+
+    // The name of the flag as used in THSaveData.
+    fn name(&self) -> &str {
+        match self {
+            Self::BiofluxVisions      => "bioflux_visions",
+            Self::JustinBailey        => "justin_bailey",
+            Self::TranslatePrimordial => "translate_primordial",
+            Self::TranslateVykhya     => "translate_vykhya",
+        }
+    }
+
+    // The name of the flag as written in the save file.
+    fn element(&self) -> &str {
+        match self {
+            Self::BiofluxVisions      => "mBiofluxVisions",
+            Self::JustinBailey        => "mJustinBailey",
+            Self::TranslatePrimordial => "mTranslatePrimordial",
+            Self::TranslateVykhya     => "mTranslateVykhya",
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self {
+            Self::BiofluxVisions      => "Bioflux visions are shown",
+            Self::JustinBailey        => "Trace plays without his lab coat",
+            Self::TranslatePrimordial => "Primordial text is translated",
+            Self::TranslateVykhya     => "Vykhya text is translated",
+        }
+    }
+
+    fn state(&self, savedata: &THSaveData) -> bool {
+        match self {
+            Self::BiofluxVisions      => savedata.bioflux_visions,
+            Self::JustinBailey        => savedata.justin_bailey,
+            Self::TranslatePrimordial => savedata.translate_primordial,
+            Self::TranslateVykhya     => savedata.translate_vykhya,
+        }
+    }
+}
+
+// A password with a known effect.
+struct KnownPassword {
+    password: &'static str,
+    effect: &'static str,
+    flag: Option<PasswordFlag>,
+}
+
+// Passwords that we know the effect of, as they're shown on the password
+// screen. JUSTIN BAILEY, the Metroid password that the game also accepts, is
+// the only one whose effect we know. Passwords for the bioflux visions and
+// translation flags aren't known, so other passwords are listed with an
+// unknown effect rather than guessed at.
+const KNOWN_PASSWORDS: &[KnownPassword] = &[
+    KnownPassword {
+        password: "JUSTIN BAILEY",
+        effect: "Trace plays without his lab coat",
+        flag: Some(PasswordFlag::JustinBailey),
+    },
+];
+
+// Passwords are entered on a grid, spaces and dashes are just padding. Only
+// used to compare passwords, they're saved as they were entered.
+fn normalise(password: &str) -> String {
+    password
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+// Returns true if the password has been entered on the password screen.
+pub fn is_entered(savedata: &THSaveData, password: &str) -> bool {
+    let normalised = normalise(password);
+
+    savedata.passwords
+        .iter()
+        .any(|entry| normalise(&entry.password) == normalised)
+}
+
+fn lookup(password: &str) -> Option<&'static KnownPassword> {
+    let password = normalise(password);
+
+    KNOWN_PASSWORDS
+        .iter()
+        .find(|known| normalise(known.password) == password)
+}
+
+// Returns the password that toggles the given flag, if we know it.
+fn password_for(flag: PasswordFlag) -> Option<&'static str> {
+    KNOWN_PASSWORDS
+        .iter()
+        .find(|known| known.flag == Some(flag))
+        .map(|known| known.password)
+}
+
+pub struct Passwords<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> Passwords<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    pub fn list(&self) {
+        let passwords = &self.savedata.passwords;
+
+        if passwords.is_empty() {
+            println!("Passwords:");
+            println!("  - No passwords entered");
+        }
+        else {
+            println!("Passwords:");
+
+            for entry in passwords {
+                let enabled = if entry.enabled {
+                    "Enabled"
+                }
+                else {
+                    "Disabled"
+                };
+
+                match lookup(&entry.password) {
+                    Some(known) => {
+                        let flag = known.flag
+                            .map(|flag| format!(" [{}]", flag.name()))
+                            .unwrap_or_default();
+
+                        println!(
+                            "  - {} ({}): {}{}",
+                            entry.password, enabled, known.effect, flag,
+                        );
+                    },
+                    None => {
+                        println!(
+                            "  - {} ({}): Unknown effect",
+                            entry.password, enabled,
+                        );
+                    },
+                }
+            }
+        }
+
+        println!("Password Flags:");
+
+        for flag in PasswordFlag::ALL {
+            let password = password_for(*flag)
+                .map(|password| format!("(set by {})", password))
+                .unwrap_or_else(|| "(no known password)".to_string());
+
+            println!(
+                "  - {}: {} - {} {}",
+                flag.name(),
+                flag.state(self.savedata),
+                flag.description(),
+                password,
+            );
+        }
+    }
+}
+
+// Sets the flag for a known password, if it has one.
+fn set_flag(document: &mut Document, password: &str, state: bool) {
+    let flag = lookup(password).and_then(|known| known.flag);
+
+    if let Some(flag) = flag {
+        if let Some(element) = document.root.child_mut(flag.element()) {
+            element.set_text(&state.to_string());
+        }
+    }
+}
+
+// Adds a password entry to the raw save data. Known passwords are saved as
+// the password screen shows them, others as given.
+pub fn add(document: &mut Document, password: &str) -> Result<()> {
+    let normalised = normalise(password);
+    let exists = document.root
+        .elements_named("PasswordEntry")
+        .filter_map(|entry| entry.child("mPassword"))
+        .any(|entry| normalise(&entry.text()) == normalised);
+
+    if exists {
+        return Err(anyhow!("Password {} has already been entered", password));
+    }
+
+    let saved = lookup(password)
+        .map_or(password.trim(), |known| known.password);
+
+    let entry = Element::new("PasswordEntry")
+        .with_child(Element::with_text("mPassword", saved))
+        .with_child(Element::with_text("mEnabled", "true"));
+
+    document.root.insert_element(entry, PASSWORD_ENTRY_FOLLOWS);
+    set_flag(document, password, true);
+
+    Ok(())
+}
+
+// Removes a password entry from the raw save data.
+pub fn remove(document: &mut Document, password: &str) -> Result<()> {
+    let normalised = normalise(password);
+    let removed = document.root.remove_elements("PasswordEntry", |entry| {
+        entry.child("mPassword")
+            .is_some_and(|entry| normalise(&entry.text()) == normalised)
+    });
+
+    if removed == 0 {
+        return Err(anyhow!("Password {} hasn't been entered", password));
+    }
+
+    set_flag(document, password, false);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{
+        save_with,
+        SAVE,
+    };
+
+    fn parse(xml: &str) -> Document {
+        Document::parse(xml.as_bytes()).unwrap()
+    }
+
+    fn passwords(document: &Document) -> Vec<String> {
+        document.root
+            .elements_named("PasswordEntry")
+            .filter_map(|entry| entry.child("mPassword"))
+            .map(|password| password.text())
+            .collect()
+    }
+
+    fn flag(document: &Document) -> String {
+        document.root.child("mJustinBailey").unwrap().text()
+    }
+
+    #[test]
+    fn adds_known_passwords_as_shown() {
+        let mut document = parse(SAVE);
+
+        add(&mut document, "justin-bailey").unwrap();
+
+        assert_eq!(passwords(&document), ["JUSTIN BAILEY"]);
+        assert_eq!(flag(&document), "true");
+    }
+
+    #[test]
+    fn adds_unknown_passwords_as_given() {
+        let mut document = parse(SAVE);
+
+        add(&mut document, " NARF NARF ").unwrap();
+
+        assert_eq!(passwords(&document), ["NARF NARF"]);
+        assert_eq!(flag(&document), "false");
+    }
+
+    #[test]
+    fn adds_before_the_following_elements() {
+        let mut document = parse(SAVE);
+
+        add(&mut document, "JUSTIN BAILEY").unwrap();
+
+        let names: Vec<&str> = document.root
+            .elements()
+            .map(|element| element.name.local_name.as_str())
+            .collect();
+        let entry = names.iter().position(|name| *name == "PasswordEntry");
+        let area = names.iter().position(|name| *name == "AreaSaveData");
+
+        assert!(entry < area);
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        let mut document = parse(SAVE);
+
+        add(&mut document, "JUSTIN BAILEY").unwrap();
+
+        assert!(add(&mut document, "justinbailey").is_err());
+        assert_eq!(passwords(&document).len(), 1);
+    }
+
+    #[test]
+    fn removes_passwords_and_clears_flags() {
+        let xml = save_with(&[
+            ("<mJustinBailey>false", "<mJustinBailey>true"),
+            (
+                "<AreaSaveData>",
+                "<PasswordEntry><mPassword>JUSTINBAILEY</mPassword>\
+                 <mEnabled>true</mEnabled></PasswordEntry>\n  \
+                 <AreaSaveData>",
+            ),
+        ]);
+        let mut document = parse(&xml);
+
+        remove(&mut document, "Justin Bailey").unwrap();
+
+        assert!(passwords(&document).is_empty());
+        assert_eq!(flag(&document), "false");
+    }
+
+    #[test]
+    fn rejects_removing_missing_passwords() {
+        let mut document = parse(SAVE);
+
+        assert!(remove(&mut document, "JUSTIN BAILEY").is_err());
+        assert_eq!(flag(&document), "false");
+    }
+
+    #[test]
+    fn looks_up_passwords_ignoring_padding() {
+        assert!(lookup("JUSTIN BAILEY").is_some());
+        assert!(lookup("justin-bailey").is_some());
+        assert!(lookup("NARF NARF").is_none());
+        assert_eq!(
+            password_for(PasswordFlag::JustinBailey),
+            Some("JUSTIN BAILEY"),
+        );
+        assert_eq!(password_for(PasswordFlag::BiofluxVisions), None);
+    }
+}
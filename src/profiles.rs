@@ -0,0 +1,378 @@
+// profiles: Select the key profile used to decrypt and encrypt save data.
+//
+// The Steam profile is built in. Other profiles can be loaded from a key file
+// or a TOML file of named profiles, for example:
+//
+//   [profiles.example]
+//   key = "baadf00d00000000203044c213e41fff"
+//   iv = "e5ffffffe5ba0700baadf00dff00ff00"
+use crate::crypto::{
+    open_input,
+    DecryptReader,
+    KeyProfile,
+    BLOCK_SIZE,
+};
+use crate::document::Document;
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{
+    prelude::*,
+    Cursor,
+};
+use std::path::Path;
+use std::str;
+
+// Profile name that tries every known profile.
+const AUTO_PROFILE: &str = "auto";
+
+// A single profile as written in a key file or profiles file.
+#[derive(Debug, Deserialize)]
+struct ProfileEntry {
+    key: String,
+    iv: String,
+}
+
+impl ProfileEntry {
+    fn into_profile(self, name: &str) -> Result<KeyProfile> {
+        let key = parse_hex(&self.key)
+            .with_context(|| format!("Invalid key in profile {}", name))?;
+        let iv = parse_hex(&self.iv)
+            .with_context(|| format!("Invalid IV in profile {}", name))?;
+
+        Ok(KeyProfile::new(name, key, iv))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfilesFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, ProfileEntry>,
+}
+
+// Parse a hex string into a key or IV. Whitespace is ignored.
+fn parse_hex(hex: &str) -> Result<[u8; BLOCK_SIZE]> {
+    let hex: Vec<u8> = hex
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+
+    if hex.len() != BLOCK_SIZE * 2 {
+        return Err(anyhow!("Expected {} hex digits", BLOCK_SIZE * 2));
+    }
+
+    // from_str_radix would also accept a sign.
+    if !hex.iter().all(u8::is_ascii_hexdigit) {
+        return Err(anyhow!("Expected only hex digits"));
+    }
+
+    let mut bytes = [0; BLOCK_SIZE];
+
+    for (byte, pair) in bytes.iter_mut().zip(hex.chunks_exact(2)) {
+        let pair = str::from_utf8(pair)?;
+        *byte = u8::from_str_radix(pair, 16)?;
+    }
+
+    Ok(bytes)
+}
+
+// Load a single profile from a key file, named after the file.
+fn load_key_file(filename: &str) -> Result<KeyProfile> {
+    let data = fs::read_to_string(filename)
+        .with_context(|| format!("Couldn't read key file {}", filename))?;
+    let entry: ProfileEntry = toml::from_str(&data)
+        .with_context(|| format!("Couldn't parse key file {}", filename))?;
+
+    let name = Path::new(filename)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(filename);
+
+    entry.into_profile(name)
+}
+
+// Load all profiles from a profiles file.
+fn load_profiles_file(filename: &str) -> Result<Vec<KeyProfile>> {
+    let data = fs::read_to_string(filename)
+        .with_context(|| format!("Couldn't read profiles {}", filename))?;
+    let file: ProfilesFile = toml::from_str(&data)
+        .with_context(|| format!("Couldn't parse profiles {}", filename))?;
+
+    file.profiles
+        .into_iter()
+        .map(|(name, entry)| entry.into_profile(&name))
+        .collect()
+}
+
+// Checks that the data decrypts to well formed XML with the given profile.
+fn decrypts_with(data: &[u8], profile: &KeyProfile) -> bool {
+    let mut reader = DecryptReader::new(data, profile);
+    let mut decrypted = Vec::new();
+
+    reader.read_to_end(&mut decrypted).is_ok()
+        && Document::parse(&decrypted).is_ok()
+}
+
+pub enum KeySelection {
+    // A single, known, profile.
+    Profile(KeyProfile),
+
+    // Try each profile in turn until one works.
+    Auto(Vec<KeyProfile>),
+}
+
+impl KeySelection {
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self> {
+        // Has a default, safe to unwrap
+        let name = matches.value_of("PROFILE").unwrap();
+
+        let key_file = matches.value_of("KEY_FILE")
+            .map(load_key_file)
+            .transpose()?;
+
+        let mut profiles: Vec<KeyProfile> = key_file
+            .into_iter()
+            .chain(Some(KeyProfile::steam()))
+            .collect();
+
+        if let Some(filename) = matches.value_of("PROFILES") {
+            profiles.extend(load_profiles_file(filename)?);
+        }
+
+        if name == AUTO_PROFILE {
+            return Ok(Self::Auto(profiles));
+        }
+
+        // A key file is used unless another profile was explicitly asked for.
+        if matches.value_of("KEY_FILE").is_some()
+            && matches.occurrences_of("PROFILE") == 0
+        {
+            return Ok(Self::Profile(profiles.remove(0)));
+        }
+
+        let profile = profiles
+            .into_iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| anyhow!("Unknown key profile: {}", name))?;
+
+        Ok(Self::Profile(profile))
+    }
+
+    // Open the input for decryption, returning the profile in use. Finding
+    // the profile automatically needs the whole input in memory.
+    pub fn decrypt_reader(
+        &self,
+        input: &str,
+    ) -> Result<(DecryptReader<Box<dyn Read>>, KeyProfile)> {
+        if let Self::Profile(profile) = self {
+            let reader = DecryptReader::new(open_input(input)?, profile);
+
+            return Ok((reader, profile.clone()));
+        }
+
+        let mut data = Vec::new();
+        open_input(input)?.read_to_end(&mut data)?;
+
+        let profile = self.profile_for(&data)
+            .ok_or_else(|| anyhow!("No key profile could decrypt {}", input))?;
+
+        let input: Box<dyn Read> = Box::new(Cursor::new(data));
+
+        Ok((DecryptReader::new(input, profile), profile.clone()))
+    }
+
+    // Returns the profile to decrypt the given data with. In auto mode this
+    // is the first profile that decrypts it to well formed XML.
+    pub fn profile_for(&self, data: &[u8]) -> Option<&KeyProfile> {
+        match self {
+            Self::Profile(profile) => Some(profile),
+            Self::Auto(profiles) => {
+                profiles
+                    .iter()
+                    .find(|profile| decrypts_with(data, profile))
+            },
+        }
+    }
+
+    // Read and decrypt a file, returning the profile that was used.
+    pub fn decrypt_file(&self, input: &str) -> Result<(Vec<u8>, KeyProfile)> {
+        let (mut reader, profile) = self.decrypt_reader(input)?;
+        let mut buffer = Vec::new();
+
+        reader.read_to_end(&mut buffer)?;
+
+        Ok((buffer, profile))
+    }
+
+    // The profile to encrypt with. Auto can't work out what to use here.
+    pub fn encryption_profile(&self) -> Result<&KeyProfile> {
+        match self {
+            Self::Profile(profile) => Ok(profile),
+            Self::Auto(_) => {
+                Err(anyhow!("A key profile must be given when encrypting"))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::EncryptWriter;
+    use crate::testdata::SAVE;
+    use tempfile::NamedTempFile;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f";
+    const IV: &str = "f0e0d0c0b0a090807060504030201000";
+
+    fn encrypt(data: &[u8], profile: &KeyProfile) -> Vec<u8> {
+        let mut writer = EncryptWriter::new(Vec::new(), profile);
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn temp_file(data: &[u8]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(data).unwrap();
+
+        file
+    }
+
+    fn other_profile() -> KeyProfile {
+        let entry = ProfileEntry {
+            key: KEY.to_string(),
+            iv: IV.to_string(),
+        };
+
+        entry.into_profile("other").unwrap()
+    }
+
+    #[test]
+    fn parses_hex() {
+        let bytes = parse_hex(KEY).unwrap();
+
+        assert_eq!(bytes[0], 0x00);
+        assert_eq!(bytes[15], 0x0f);
+        assert_eq!(
+            parse_hex("00010203 04050607\n08090A0B 0C0D0E0F").unwrap(),
+            bytes,
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_length_hex() {
+        let error = parse_hex("0001").unwrap_err().to_string();
+
+        assert_eq!(error, "Expected 32 hex digits");
+        assert!(parse_hex(&format!("{}00", KEY)).is_err());
+        assert!(parse_hex("").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_hex() {
+        assert!(parse_hex("zz0102030405060708090a0b0c0d0e0f").is_err());
+        assert!(parse_hex("+f0102030405060708090a0b0c0d0e0f").is_err());
+    }
+
+    #[test]
+    fn loads_profiles_files() {
+        let toml = format!(
+            "[profiles.one]\nkey = \"{}\"\niv = \"{}\"\n\n\
+             [profiles.two]\nkey = \"{}\"\niv = \"{}\"\n",
+            KEY, IV, IV, KEY,
+        );
+        let file = temp_file(toml.as_bytes());
+        let profiles = load_profiles_file(file.path().to_str().unwrap())
+            .unwrap();
+
+        let names: Vec<&str> = profiles
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["one", "two"]);
+    }
+
+    #[test]
+    fn names_bad_profiles() {
+        let toml = format!(
+            "[profiles.broken]\nkey = \"00\"\niv = \"{}\"\n",
+            IV,
+        );
+        let file = temp_file(toml.as_bytes());
+        let error = load_profiles_file(file.path().to_str().unwrap())
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(error, "Invalid key in profile broken");
+    }
+
+    #[test]
+    fn names_key_files_after_the_file() {
+        let toml = format!("key = \"{}\"\niv = \"{}\"\n", KEY, IV);
+        let file = tempfile::Builder::new()
+            .prefix("mykey")
+            .suffix(".toml")
+            .tempfile()
+            .unwrap();
+        std::fs::write(file.path(), toml).unwrap();
+
+        let profile = load_key_file(file.path().to_str().unwrap()).unwrap();
+
+        assert!(profile.name.starts_with("mykey"));
+    }
+
+    #[test]
+    fn auto_detects_the_profile() {
+        let keys = KeySelection::Auto(vec![
+            KeyProfile::steam(),
+            other_profile(),
+        ]);
+        let data = encrypt(SAVE.as_bytes(), &other_profile());
+
+        assert_eq!(keys.profile_for(&data).unwrap().name, "other");
+
+        let file = temp_file(&data);
+        let (decrypted, profile) = keys
+            .decrypt_file(file.path().to_str().unwrap())
+            .unwrap();
+
+        assert_eq!(profile.name, "other");
+        assert_eq!(decrypted, SAVE.as_bytes());
+    }
+
+    #[test]
+    fn auto_detect_fails_when_nothing_decrypts() {
+        let keys = KeySelection::Auto(vec![KeyProfile::steam()]);
+        let data = encrypt(SAVE.as_bytes(), &other_profile());
+        let file = temp_file(&data);
+        let filename = file.path().to_str().unwrap();
+
+        assert!(keys.profile_for(&data).is_none());
+
+        let error = keys.decrypt_file(filename).unwrap_err().to_string();
+
+        assert_eq!(error, format!("No key profile could decrypt {}", filename));
+    }
+
+    #[test]
+    fn auto_detect_needs_well_formed_xml() {
+        let keys = KeySelection::Auto(vec![KeyProfile::steam()]);
+        let data = encrypt(b"not xml", &KeyProfile::steam());
+
+        assert!(keys.profile_for(&data).is_none());
+    }
+
+    #[test]
+    fn auto_cant_encrypt() {
+        let keys = KeySelection::Auto(vec![KeyProfile::steam()]);
+
+        assert!(keys.encryption_profile().is_err());
+    }
+}
@@ -0,0 +1,69 @@
+// progress: Main story progress, from the key points in the save data.
+use crate::keypoints::{
+    self,
+    MILESTONES,
+};
+use crate::savedata::THSaveData;
+
+pub struct Progress<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    // Prints the main story checklist, followed by any other key points.
+    pub fn report(&self) {
+        let done = MILESTONES
+            .iter()
+            .filter(|milestone| milestone.is_done(self.savedata))
+            .count();
+
+        let percent = done as f32 / MILESTONES.len() as f32 * 100.0;
+
+        println!(
+            "Story Progress: {}/{} milestones ({:.2}%)",
+            done,
+            MILESTONES.len(),
+            percent,
+        );
+
+        for milestone in MILESTONES {
+            let status = if milestone.is_done(self.savedata) {
+                "Done"
+            }
+            else {
+                "Remaining"
+            };
+
+            println!("  - {}: {}", milestone.name, status);
+        }
+
+        // Key points outside of the main story, including any we don't know.
+        let others: Vec<&String> = self.savedata.key_points_completed
+            .iter()
+            .filter(|key_point| {
+                !MILESTONES
+                    .iter()
+                    .any(|milestone| milestone.is_key_point(key_point))
+            })
+            .collect();
+
+        if others.is_empty() {
+            return;
+        }
+
+        println!("Other key points:");
+
+        for key_point in others {
+            match keypoints::describe(key_point) {
+                Some(name) => println!("  - {} ({})", name, key_point),
+                None       => println!("  - {}", key_point),
+            }
+        }
+    }
+}
@@ -0,0 +1,297 @@
+// randomizer: Inspect the item placements of randomizer seeds.
+use crate::items;
+use crate::savedata::THSaveData;
+use anyhow::{
+    anyhow,
+    Result,
+};
+use std::str;
+
+// Area used for locations that we can't place.
+const UNKNOWN_AREA: &str = "Unknown";
+
+// A single randomized item location.
+struct Placement<'a> {
+    location: &'a str,
+    item: &'a str,
+    area: &'a str,
+    collected: bool,
+}
+
+pub struct Randomizer<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> Randomizer<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Result<Self> {
+        if savedata.is_randomizer != Some(true) {
+            return Err(anyhow!("Save game is not from a randomizer seed"));
+        }
+
+        Ok(Self {
+            savedata,
+        })
+    }
+
+    // Finds the area a location is in. Areas record which locations have
+    // been collected in them, so prefer that and fall back to the catalogue
+    // for locations that haven't been visited yet.
+    fn area_of(&self, location: &str) -> &'a str {
+        let visited = self.savedata.area_save_data
+            .iter()
+            .find(|area| {
+                area.items
+                    .as_ref()
+                    .is_some_and(|items| items.contains(location))
+            });
+
+        if let Some(area) = visited {
+            &area.area_name
+        }
+        else if let Some(item) = items::lookup(location) {
+            item.area
+        }
+        else {
+            UNKNOWN_AREA
+        }
+    }
+
+    // Returns all placements, sorted by area and then location name.
+    fn placements(&self) -> Vec<Placement<'a>> {
+        let random_item = match &self.savedata.random_item {
+            Some(random_item) => random_item,
+            None              => return Vec::new(),
+        };
+
+        let mut placements: Vec<Placement> = random_item
+            .iter()
+            .map(|(location, item)| {
+                Placement {
+                    location,
+                    item,
+                    area: self.area_of(location),
+                    collected: self.collected(location),
+                }
+            })
+            .collect();
+
+        placements.sort_by(|a, b| {
+            let a_key = (items::area_order(a.area), a.area, a.location);
+            let b_key = (items::area_order(b.area), b.area, b.location);
+
+            a_key.cmp(&b_key)
+        });
+
+        placements
+    }
+
+    // A location is collected if any area lists it. Holding the item placed
+    // there says nothing, as the same item can be placed in more than one
+    // location.
+    fn collected(&self, location: &str) -> bool {
+        self.savedata.area_save_data
+            .iter()
+            .filter_map(|area| area.items.as_ref())
+            .any(|items| items.contains(location))
+    }
+
+    fn header(&self) {
+        let seed = self.savedata.randomizer_seed
+            .as_deref()
+            .unwrap_or("Unknown");
+
+        match &self.savedata.randomizer_difficulty {
+            Some(difficulty) => {
+                println!("Randomizer Seed: {} ({})", seed, difficulty);
+            },
+            None => {
+                println!("Randomizer Seed: {}", seed);
+            },
+        }
+    }
+
+    // Lists only the collected locations, so that a seed can be shown on
+    // stream without spoiling anything.
+    pub fn track(&self) {
+        self.header();
+
+        let placements = self.placements();
+        let found = placements
+            .iter()
+            .filter(|p| p.collected)
+            .count();
+
+        println!("Found {}/{} items:", found, placements.len());
+
+        if placements.is_empty() {
+            println!("  - No locations to show");
+            return;
+        }
+
+        let width = placements
+            .iter()
+            .filter(|p| p.collected)
+            .map(|p| items::display_name(p.location).len())
+            .max()
+            .unwrap_or(0);
+
+        // Placements are sorted by area, so chunk them up.
+        for area in placements.chunk_by(|a, b| a.area == b.area) {
+            let found = area
+                .iter()
+                .filter(|p| p.collected)
+                .count();
+
+            println!("{}: {}/{}", area[0].area, found, area.len());
+
+            for placement in area.iter().filter(|p| p.collected) {
+                println!(
+                    "  - {:width$} -> {}",
+                    items::display_name(placement.location),
+                    items::display_name(placement.item),
+                    width = width,
+                );
+            }
+        }
+    }
+
+    // Full spoiler log of every location, optionally limited to locations
+    // that haven't been collected yet.
+    pub fn spoiler(&self, remaining_only: bool) {
+        self.header();
+
+        let placements: Vec<Placement> = self.placements()
+            .into_iter()
+            .filter(|p| !remaining_only || !p.collected)
+            .collect();
+
+        if placements.is_empty() {
+            println!("  - No locations to show");
+            return;
+        }
+
+        // Line up the item column.
+        let width = placements
+            .iter()
+            .map(|p| items::display_name(p.location).len())
+            .max()
+            .unwrap_or(0);
+
+        let mut current_area = None;
+
+        for placement in placements {
+            if current_area != Some(placement.area) {
+                println!("{}:", placement.area);
+                current_area = Some(placement.area);
+            }
+
+            let state = if placement.collected {
+                "Collected"
+            }
+            else {
+                "Not collected"
+            };
+
+            println!(
+                "  - {:width$} -> {} ({})",
+                items::display_name(placement.location),
+                items::display_name(placement.item),
+                state,
+                width = width,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{
+        save_with,
+        savedata,
+    };
+
+    // The test save as a randomizer seed, with the given placements. Only
+    // the Axiom Disruptor location has been collected, in Eribu.
+    fn seed(placements: &str) -> String {
+        let random = format!(
+            "<mIsRandomizer>true</mIsRandomizer>\n  \
+             <mRandomItem>{}</mRandomItem>\n  <mUseRealTimers>",
+            placements,
+        );
+
+        save_with(&[("<mUseRealTimers>", &random)])
+    }
+
+    const PLACEMENTS: &str = "<AxiomDisruptor>Kilver</AxiomDisruptor>\
+                              <AddressDisruptor>Nova</AddressDisruptor>\
+                              <NotALocation>AxiomDisruptor</NotALocation>";
+
+    #[test]
+    fn needs_a_randomizer_seed() {
+        let normal = savedata(&save_with(&[]));
+        let not_random = savedata(&save_with(&[(
+            "<mUseRealTimers>",
+            "<mIsRandomizer>false</mIsRandomizer>\n  <mUseRealTimers>",
+        )]));
+
+        assert!(Randomizer::new(&normal).is_err());
+        assert!(Randomizer::new(&not_random).is_err());
+    }
+
+    #[test]
+    fn counts_collected_locations() {
+        let savedata = savedata(&seed(PLACEMENTS));
+        let randomizer = Randomizer::new(&savedata).unwrap();
+        let placements = randomizer.placements();
+
+        let collected: Vec<&str> = placements
+            .iter()
+            .filter(|p| p.collected)
+            .map(|p| p.location)
+            .collect();
+
+        assert_eq!(placements.len(), 3);
+        assert_eq!(collected, ["AxiomDisruptor"]);
+    }
+
+    #[test]
+    fn holding_an_item_doesnt_collect_its_location() {
+        // The save holds the Address Disruptor, but no area lists its
+        // location as collected.
+        let savedata = savedata(&seed(PLACEMENTS));
+        let randomizer = Randomizer::new(&savedata).unwrap();
+
+        assert!(savedata.items.iter().any(|i| i.name == "AddressDisruptor"));
+        assert!(!randomizer.collected("AddressDisruptor"));
+        assert!(randomizer.collected("AxiomDisruptor"));
+    }
+
+    #[test]
+    fn places_locations_in_areas() {
+        let savedata = savedata(&seed(PLACEMENTS));
+        let randomizer = Randomizer::new(&savedata).unwrap();
+
+        assert_eq!(randomizer.area_of("AxiomDisruptor"), "Eribu");
+        assert_eq!(
+            randomizer.area_of("AddressDisruptor"),
+            items::lookup("AddressDisruptor").unwrap().area,
+        );
+        assert_eq!(randomizer.area_of("NotALocation"), UNKNOWN_AREA);
+
+        // Unknown locations sort last.
+        let placements = randomizer.placements();
+        assert_eq!(placements.last().unwrap().location, "NotALocation");
+    }
+
+    #[test]
+    fn seeds_without_placements_are_empty() {
+        let xml = save_with(&[(
+            "<mUseRealTimers>",
+            "<mIsRandomizer>true</mIsRandomizer>\n  <mUseRealTimers>",
+        )]);
+        let savedata = savedata(&xml);
+
+        assert!(Randomizer::new(&savedata).unwrap().placements().is_empty());
+    }
+}
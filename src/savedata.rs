@@ -1,5 +1,16 @@
 // savedata: Save data structs and impl methods.
-use serde::Deserialize;
+//
+// The structs here mirror the full save file, so some fields are never read.
+// Those fields, and the structs only there to mirror the file, allow dead code
+// individually so that anything else unused still gets a warning.
+use anyhow::{
+    anyhow,
+    Error,
+};
+use serde::{
+    Deserialize,
+    Deserializer,
+};
 use std::collections::{
     HashMap,
     HashSet,
@@ -10,8 +21,98 @@ use std::str;
 // Helper type for the SaveData structs
 type SerializableDictionary = HashMap<String, String>;
 
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
-pub enum Creature {
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CreatureCategory {
+    Fauna,
+    Flora,
+    Mechanized,
+    Other,
+    Unknown,
+}
+
+impl fmt::Display for CreatureCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::Fauna      => "Fauna",
+            Self::Flora      => "Flora",
+            Self::Mechanized => "Mechanized",
+            Self::Other      => "Other",
+            Self::Unknown    => "Unknown",
+        };
+
+        write!(f, "{}", desc)
+    }
+}
+
+impl str::FromStr for CreatureCategory {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fauna"      => Ok(Self::Fauna),
+            "flora"      => Ok(Self::Flora),
+            "mechanized" => Ok(Self::Mechanized),
+            "other"      => Ok(Self::Other),
+            "unknown"    => Ok(Self::Unknown),
+            _            => Err(anyhow!("Unknown creature category: {}", s)),
+        }
+    }
+}
+
+// Defines the Creature enum along with Creature::ALL, a list of every known
+// creature. Both come from the same list, so ALL can never miss a creature.
+//
+// Each creature is listed by variant, optionally followed by the ID used in
+// the save data if it differs, and any other IDs it's known by.
+macro_rules! creatures {
+    ($($variant:ident $(= $id:literal)? $(| $alias:literal)*,)*) => {
+        #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+        pub enum Creature {
+            $($variant,)*
+
+            // A creature we don't know about, keeping the ID from the save.
+            Unknown(String),
+        }
+
+        impl Creature {
+            // Every known creature, in the order that they're declared.
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)*];
+
+            // Returns the ID of the creature, as used in the save data.
+            pub fn id(&self) -> &str {
+                match self {
+                    $(Self::$variant => creature_id!($variant $(, $id)?),)*
+                    Self::Unknown(id) => id,
+                }
+            }
+
+            // Returns the creature with the given save data ID.
+            pub fn from_id(id: &str) -> Self {
+                $(
+                    if id == creature_id!($variant $(, $id)?)
+                        $(|| id == $alias)*
+                    {
+                        return Self::$variant;
+                    }
+                )*
+
+                Self::Unknown(id.to_string())
+            }
+        }
+    };
+}
+
+// The save data ID of a creature, which is the variant name unless given.
+macro_rules! creature_id {
+    ($variant:ident) => {
+        stringify!($variant)
+    };
+    ($variant:ident, $id:literal) => {
+        $id
+    };
+}
+
+creatures! {
     // Fauna
     Arachnoptopus,
     Artichoker,
@@ -30,17 +131,21 @@ pub enum Creature {
     Jorm,
     Jormite,
     LoopDiatom,
+    LoopDiatomViolet = "LoopDiatom_Violet",
     Mogra,
     Mutant,
+    MutantStrong     = "Mutant_Strong",
     Pliaa,
     Potato,
     Prongfish,
     Quadropus,
     Rugg,
+    RuggMeta         = "Rugg_Meta",
     Scorpiant,
     Seamk,
     SmallMogra,
     Snailborg,
+    SnailborgMeta    = "Snailborg_Meta",
     SpaceBat,
     Spidler,
     Spiru,
@@ -49,32 +154,15 @@ pub enum Creature {
     SwarmilyChild,
     SwarmilyParent,
     TrapClaw,
+    TrapClawGamma    = "TrapClaw_Gamma",
+    TrapClawMeta     = "TrapClaw_Meta",
+
+    // TubePuff is known by two names.
+    TubePuff | "TubeWorm_Meta",
     TubeWorm,
     Volg,
     Yorchug,
 
-    // TubePuff is known by two names.
-    #[serde(alias = "TubeWorm_Meta")]
-    TubePuff,
-
-    #[serde(rename = "LoopDiatom_Violet")]
-    LoopDiatomViolet,
-
-    #[serde(rename = "Mutant_Strong")]
-    MutantStrong,
-
-    #[serde(rename = "Rugg_Meta")]
-    RuggMeta,
-
-    #[serde(rename = "Snailborg_Meta")]
-    SnailborgMeta,
-
-    #[serde(rename = "TrapClaw_Gamma")]
-    TrapClawGamma,
-
-    #[serde(rename = "TrapClaw_Meta")]
-    TrapClawMeta,
-
     // Flora
     // DragWeed
     Goolumn,
@@ -91,20 +179,31 @@ pub enum Creature {
     Hoverbug,
     RepairDrone,
     SentryBot,
+    SentryBotMeta = "SentryBot_Meta",
     TieFlighter,
 
-    #[serde(rename = "SentryBot_Meta")]
-    SentryBotMeta,
-
     // Other
     Nrok,
     SpitbugNest,
 }
 
-impl<'a> Creature {
+// Creatures are deserialized by hand so that unknown IDs don't stop the whole
+// save from loading.
+impl<'de> Deserialize<'de> for Creature {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let id = String::deserialize(deserializer)?;
+
+        Ok(Self::from_id(&id))
+    }
+}
+
+impl Creature {
     // Returns the real name of the creature as listed on
     // https://axiom-verge.fandom.com/wiki/Category:Bestiary
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         match self {
             // Fauna
             Self::Arachnoptopus    => "Hopping Spider",
@@ -176,89 +275,172 @@ impl<'a> Creature {
             // Other
             Self::Nrok        => "Boulder",
             Self::SpitbugNest => "Hive",
+
+            // Not in the bestiary, all we have is the ID.
+            Self::Unknown(id) => id,
         }
     }
 
-    // Returns a Vec of all creatures required for hacker achievement.
-    pub fn achievement_list() -> &'a [Self] {
-        &[
+    // Returns the bestiary category of the creature. Every creature is listed,
+    // so that a new one can't be put in a category by default.
+    pub fn category(&self) -> CreatureCategory {
+        match self {
+            Self::Arachnoptopus
+            | Self::Artichoker
+            | Self::Blite
+            | Self::Blurst
+            | Self::BlurstSpawn
+            | Self::Buoyg
+            | Self::Drometon
+            | Self::EyeCopter
+            | Self::FlynnStone
+            | Self::Fungine
+            | Self::Furglot
+            | Self::Gill
+            | Self::Glugg
+            | Self::Hookfish
+            | Self::Jorm
+            | Self::Jormite
+            | Self::LoopDiatom
+            | Self::LoopDiatomViolet
+            | Self::Mogra
+            | Self::Mutant
+            | Self::MutantStrong
+            | Self::Pliaa
+            | Self::Potato
+            | Self::Prongfish
+            | Self::Quadropus
+            | Self::Rugg
+            | Self::RuggMeta
+            | Self::Scorpiant
+            | Self::Seamk
+            | Self::SmallMogra
+            | Self::Snailborg
+            | Self::SnailborgMeta
+            | Self::SpaceBat
+            | Self::Spidler
+            | Self::Spiru
+            | Self::SpitBug
+            | Self::SpitBugBossSpawn
+            | Self::SwarmilyChild
+            | Self::SwarmilyParent
+            | Self::TrapClaw
+            | Self::TrapClawGamma
+            | Self::TrapClawMeta
+            | Self::TubePuff
+            | Self::TubeWorm
+            | Self::Volg
+            | Self::Yorchug => CreatureCategory::Fauna,
+
+            Self::Goolumn
+            | Self::Hoverling
+            | Self::MushroomPoof
+            | Self::SpungusSpore
+            | Self::TentacleGrass
+            | Self::WillOWisp => CreatureCategory::Flora,
+
+            Self::Annihiwaiter
+            | Self::Diskko
+            | Self::Donaught
+            | Self::Hoverbug
+            | Self::RepairDrone
+            | Self::SentryBot
+            | Self::SentryBotMeta
+            | Self::TieFlighter => CreatureCategory::Mechanized,
+
+            Self::Nrok
+            | Self::SpitbugNest => CreatureCategory::Other,
+
+            Self::Unknown(_) => CreatureCategory::Unknown,
+        }
+    }
+
+    // Returns all creatures required for the Hacker achievement.
+    pub fn achievement_list() -> Vec<Self> {
+        Self::ALL
+            .iter()
+            .filter(|creature| creature.hacker())
+            .cloned()
+            .collect()
+    }
+
+    // Returns true or false depending if the creature is required for the
+    // Hacker achievement. There's deliberately no wildcard here, so that new
+    // creatures have to be classified.
+    pub fn hacker(&self) -> bool {
+        match self {
+            Self::Jormite | Self::TentacleGrass | Self::RepairDrone => false,
+
+            // We can't know if the game needs creatures we don't know about.
+            Self::Unknown(_) => false,
+
             // Fauna
-            Self::Arachnoptopus,
-            Self::Artichoker,
-            Self::Blite,
-            Self::Blurst,
-            Self::BlurstSpawn,
-            Self::Buoyg,
-            Self::Drometon,
-            Self::EyeCopter,
-            Self::FlynnStone,
-            Self::Fungine,
-            Self::Furglot,
-            Self::Gill,
-            Self::Glugg,
-            Self::Hookfish,
-            Self::Jorm,
-            Self::LoopDiatom,
-            Self::LoopDiatomViolet,
-            Self::Mogra,
-            Self::Mutant,
-            Self::MutantStrong,
-            Self::Pliaa,
-            Self::Potato,
-            Self::Prongfish,
-            Self::Quadropus,
-            Self::Rugg,
-            Self::RuggMeta,
-            Self::Scorpiant,
-            Self::Seamk,
-            Self::SmallMogra,
-            Self::Snailborg,
-            Self::SnailborgMeta,
-            Self::SpaceBat,
-            Self::Spidler,
-            Self::Spiru,
-            Self::SpitBug,
-            Self::SpitBugBossSpawn,
-            Self::SwarmilyChild,
-            Self::SwarmilyParent,
-            Self::TrapClaw,
-            Self::TrapClawGamma,
-            Self::TrapClawMeta,
-            Self::TubePuff,
-            Self::TubeWorm,
-            Self::Volg,
-            Self::Yorchug,
+            Self::Arachnoptopus
+            | Self::Artichoker
+            | Self::Blite
+            | Self::Blurst
+            | Self::BlurstSpawn
+            | Self::Buoyg
+            | Self::Drometon
+            | Self::EyeCopter
+            | Self::FlynnStone
+            | Self::Fungine
+            | Self::Furglot
+            | Self::Gill
+            | Self::Glugg
+            | Self::Hookfish
+            | Self::Jorm
+            | Self::LoopDiatom
+            | Self::Mogra
+            | Self::Mutant
+            | Self::Pliaa
+            | Self::Potato
+            | Self::Prongfish
+            | Self::Quadropus
+            | Self::Rugg
+            | Self::Scorpiant
+            | Self::Seamk
+            | Self::SmallMogra
+            | Self::Snailborg
+            | Self::SpaceBat
+            | Self::Spidler
+            | Self::Spiru
+            | Self::SpitBug
+            | Self::SpitBugBossSpawn
+            | Self::SwarmilyChild
+            | Self::SwarmilyParent
+            | Self::TrapClaw
+            | Self::TubeWorm
+            | Self::Volg
+            | Self::Yorchug
+            | Self::TubePuff
+            | Self::LoopDiatomViolet
+            | Self::MutantStrong
+            | Self::RuggMeta
+            | Self::SnailborgMeta
+            | Self::TrapClawGamma
+            | Self::TrapClawMeta
 
             // Flora
-            Self::Goolumn,
-            Self::Hoverling,
-            Self::MushroomPoof,
-            Self::SpungusSpore,
-            Self::WillOWisp,
+            | Self::Goolumn
+            | Self::Hoverling
+            | Self::MushroomPoof
+            | Self::SpungusSpore
+            | Self::WillOWisp
 
             // Mechanized
-            Self::Annihiwaiter,
-            Self::Diskko,
-            Self::Donaught,
-            Self::Hoverbug,
-            Self::SentryBot,
-            Self::SentryBotMeta,
-            Self::TieFlighter,
+            | Self::Annihiwaiter
+            | Self::Diskko
+            | Self::Donaught
+            | Self::Hoverbug
+            | Self::SentryBot
+            | Self::TieFlighter
+            | Self::SentryBotMeta
 
             // Other
-            Self::Nrok,
-            Self::SpitbugNest,
-        ]
-    }
-
-    // Returns true or false depending if the Fauna is required for the Hacker
-    // achievement.
-    fn hacker(&self) -> bool {
-        // Most creatures are required, just call out creatures that aren't.
-        !matches!(
-            self,
-            Self::Jormite | Self::TentacleGrass | Self::RepairDrone
-        )
+            | Self::Nrok
+            | Self::SpitbugNest => true,
+        }
     }
 }
 
@@ -269,7 +451,7 @@ impl fmt::Display for Creature {
 }
 
 #[derive(Debug, Deserialize)]
-enum RandomizerDifficultySetting {
+pub enum RandomizerDifficultySetting {
     #[serde(rename = "DEFAULT")]
     Default,
 
@@ -289,6 +471,21 @@ enum RandomizerDifficultySetting {
     Hard,
 }
 
+impl fmt::Display for RandomizerDifficultySetting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::Default   => "Default",
+            Self::Advanced  => "Advanced",
+            Self::Masochist => "Masochist",
+            Self::Easy      => "Easy",
+            Self::Normal    => "Normal",
+            Self::Hard      => "Hard",
+        };
+
+        write!(f, "{}", desc)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 enum THCollisionDirs {
     None,
@@ -299,7 +496,7 @@ enum THCollisionDirs {
 }
 
 #[derive(Debug, Deserialize)]
-enum THDifficultySetting {
+pub enum THDifficultySetting {
     #[serde(rename = "NORMAL")]
     Normal,
 
@@ -307,7 +504,18 @@ enum THDifficultySetting {
     Hard,
 }
 
-#[derive(Debug, Deserialize, Eq, PartialEq)]
+impl fmt::Display for THDifficultySetting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::Normal => "Normal",
+            Self::Hard   => "Hard",
+        };
+
+        write!(f, "{}", desc)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
 pub enum THItemType {
     #[serde(rename = "GLITCH_BOMB_DROP")]
     GlitchBombDrop,
@@ -343,12 +551,28 @@ pub enum THItemType {
     Tool,
 
     #[serde(rename = "WEAPON")]
+    #[default]
     Weapon,
 }
 
-impl Default for THItemType {
-    fn default() -> Self {
-        Self::Weapon
+impl fmt::Display for THItemType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::GlitchBombDrop     => "Glitch Bomb Drop",
+            Self::HealthNode         => "Health Node",
+            Self::HealthNodeFragment => "Health Node Fragment",
+            Self::HealthDrop         => "Health Drop",
+            Self::Lore               => "Note",
+            Self::PermanentUpgrade   => "Upgrade",
+            Self::PowerNode          => "Power Node",
+            Self::PowerNodeFragment  => "Power Node Fragment",
+            Self::RangeNode          => "Range Node",
+            Self::SizeNode           => "Size Node",
+            Self::Tool               => "Tool",
+            Self::Weapon             => "Weapon",
+        };
+
+        write!(f, "{}", desc)
     }
 }
 
@@ -377,6 +601,7 @@ enum THMapScreenSubScreen {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct Point {
     #[serde(rename = "X")]
     x: i32,
@@ -386,61 +611,72 @@ struct Point {
 }
 
 #[derive(Debug, Deserialize)]
-struct THAreaSaveData {
+pub struct THAreaSaveData {
     #[serde(rename = "mAreaName")]
-    area_name: String,
+    pub area_name: String,
 
     #[serde(rename = "mSeed")]
-    seed: i32,
+    pub seed: i32,
 
     #[serde(rename = "mScreenCount")]
-    screen_count: i32,
+    pub screen_count: i32,
 
+    #[allow(dead_code)]
     #[serde(rename = "mX")]
     x: f32,
 
+    #[allow(dead_code)]
     #[serde(rename = "mY")]
     y: f32,
 
     #[serde(rename = "mItem")]
-    items: Option<HashSet<String>>,
+    pub items: Option<HashSet<String>>,
 }
 
 #[derive(Debug, Deserialize)]
-struct THAutoMapData {
+pub struct THAutoMapData {
     //MAX_REMINDERS: i32,
     #[serde(rename = "mAreaName")]
-    area_name: String,
+    pub area_name: String,
 
+    #[allow(dead_code)]
     #[serde(rename = "mWidthScreens")]
     width_screens: i32,
 
+    #[allow(dead_code)]
     #[serde(rename = "mHeightScreens")]
     height_screens: i32,
 
     #[serde(rename = "mScreenCount")]
-    screen_count: i32,
+    pub screen_count: i32,
 
+    #[allow(dead_code)]
     #[serde(rename = "mCSVData")]
     csv_data: String,
 
+    #[allow(dead_code)]
     #[serde(rename = "mData")]
     data: Option<Vec<u32>>,
 
+    #[allow(dead_code)]
     #[serde(rename = "Entrance")]
     entrances: Option<Vec<THAutoMapDoor>>,
 
+    #[allow(dead_code)]
     #[serde(rename = "Door")]
     doors: Option<Vec<THAutoMapDoor>>,
 
+    #[allow(dead_code)]
     #[serde(rename = "Room")]
     rooms: Option<Vec<THAutoMapRoom>>,
 
+    #[allow(dead_code)]
     #[serde(rename = "Reminder")]
     reminders: Vec<Point>,
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct THAutoMapDoor {
     #[serde(rename = "mX")]
     x: i32,
@@ -453,6 +689,7 @@ struct THAutoMapDoor {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct THAutoMapRoom {
     #[serde(rename = "mX")]
     x: i32,
@@ -470,11 +707,12 @@ struct THAutoMapRoom {
 #[derive(Debug, Deserialize)]
 pub struct THItemRecord {
     #[serde(rename = "mName")]
-    name: String,
+    pub name: String,
 
     #[serde(rename = "mType")]
     pub type_: THItemType,
 
+    #[allow(dead_code)]
     #[serde(rename = "mConsumable")]
     consumable: bool,
 
@@ -482,31 +720,31 @@ pub struct THItemRecord {
     pub excluded_from_count: bool,
 
     #[serde(rename = "mRequiredItem")]
-    required_item: Option<String>,
+    pub required_item: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
-struct THPasswordSaveEntry {
+pub struct THPasswordSaveEntry {
     #[serde(rename = "mPassword")]
-    password: String,
+    pub password: String,
 
     #[serde(rename = "mEnabled")]
-    enabled: bool,
+    pub enabled: bool,
 }
 
 #[derive(Debug, Deserialize)]
-struct THSecretWorldSaveData {
+pub struct THSecretWorldSaveData {
     #[serde(rename = "mAreaName")]
-    area_name: String,
+    pub area_name: String,
 
     #[serde(rename = "mSecretWorldName")]
-    secret_world_name: String,
+    pub secret_world_name: String,
 
     #[serde(rename = "mPrimaryItem")]
-    primary_item: String,
+    pub primary_item: String,
 
     #[serde(rename = "mSecondaryItem")]
-    secondary_item: String,
+    pub secondary_item: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -515,10 +753,11 @@ pub struct THSpeedrunCheckpoint {
     pub name: String,
 
     #[serde(rename = "mFrames")]
-    frames: i64,
+    pub frames: i64,
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct Vector2 {
     #[serde(rename = "X")]
     x: i32,
@@ -530,38 +769,46 @@ struct Vector2 {
 // Save data structure
 #[derive(Debug, Deserialize)]
 pub struct THSaveData {
+    #[allow(dead_code)]
     #[serde(rename = "mScreenSize")]
     screen_size: i32,
 
+    #[allow(dead_code)]
     #[serde(rename = "mPlayerName")]
     player_name: String,
 
     #[serde(rename = "mDifficulty")]
-    difficulty: THDifficultySetting,
+    pub difficulty: THDifficultySetting,
 
     #[serde(rename = "mRandomizerDifficulty")]
-    randomizer_difficulty: Option<RandomizerDifficultySetting>,
+    pub randomizer_difficulty: Option<RandomizerDifficultySetting>,
 
+    #[allow(dead_code)]
     #[serde(rename = "mCurrentWeapon")]
     current_weapon: String,
 
+    #[allow(dead_code)]
     #[serde(rename = "mPreviousWeapon")]
     previous_weapon: Option<String>,
 
+    #[allow(dead_code)]
     #[serde(rename = "mCurrentTool")]
     current_tool: Option<String>,
 
+    #[allow(dead_code)]
     #[serde(rename = "mSaveArea")]
     save_area: String,
 
+    #[allow(dead_code)]
     #[serde(rename = "mSaveRoom")]
     save_room: String,
 
+    #[allow(dead_code)]
     #[serde(rename = "mSaveRoomPos")]
     save_room_pos: Vector2,
 
     #[serde(rename = "mTotalFrames")]
-    total_frames: i64,
+    pub total_frames: i64,
 
     #[serde(rename = "mEffectiveFrames")]
     pub effective_frames: f64,
@@ -582,59 +829,68 @@ pub struct THSaveData {
     pub bricks_destroyed: i32,
 
     #[serde(rename = "mIsSpeedRun")]
-    is_speed_run: bool,
+    pub is_speed_run: bool,
 
     #[serde(rename = "mIsRandomizer")]
-    is_randomizer: Option<bool>,
+    pub is_randomizer: Option<bool>,
 
     #[serde(rename = "mRandomItem")]
-    random_item: Option<SerializableDictionary>,
+    pub random_item: Option<SerializableDictionary>,
 
     #[serde(rename = "mUseRealTimers")]
-    use_real_timers: bool,
+    pub use_real_timers: bool,
 
+    #[allow(dead_code)]
     #[serde(rename = "mLastMapSubScreen")]
     last_map_sub_screen: THMapScreenSubScreen,
 
+    #[allow(dead_code)]
     #[serde(rename = "mBaseSeed")]
     base_seed: i32,
 
     #[serde(rename = "mRandomizerSeed")]
-    randomizer_seed: Option<String>,
+    pub randomizer_seed: Option<String>,
 
     #[serde(rename = "mBiofluxVisions")]
-    bioflux_visions: bool,
+    pub bioflux_visions: bool,
 
+    #[allow(dead_code)]
     #[serde(rename = "mHallucinationAmount")]
     hallucination_amount: f32,
 
     #[serde(rename = "mTranslatePrimordial")]
-    translate_primordial: bool,
+    pub translate_primordial: bool,
 
     #[serde(rename = "mTranslateVykhya")]
-    translate_vykhya: bool,
+    pub translate_vykhya: bool,
 
     #[serde(rename = "mJustinBailey")]
-    justin_bailey: bool,
+    pub justin_bailey: bool,
 
+    #[allow(dead_code)]
     #[serde(rename = "mTraceBlues")]
     trace_blues: Option<bool>,
 
+    #[allow(dead_code)]
     #[serde(rename = "mTraceBlack")]
     trace_black: Option<bool>,
 
+    #[allow(dead_code)]
     #[serde(rename = "mTraceYellow")]
     trace_yellow: Option<bool>,
 
+    #[allow(dead_code)]
     #[serde(rename = "mSecretWindow")]
     secret_window: Option<bool>,
 
+    #[allow(dead_code)]
     #[serde(rename = "mHasDrone")]
     has_drone: bool,
 
     #[serde(rename = "mCheatsUsed")]
-    cheats_used: bool,
+    pub cheats_used: bool,
 
+    #[allow(dead_code)]
     #[serde(rename = "QuickSelectWeapon")]
     weapon_quick_select: Vec<String>,
 
@@ -642,19 +898,19 @@ pub struct THSaveData {
     pub items: Vec<THItemRecord>,
 
     #[serde(rename = "KeyPoint")]
-    key_points_completed: Vec<String>,
+    pub key_points_completed: Vec<String>,
 
-    #[serde(rename = "PasswordEntry")]
-    passwords: Vec<THPasswordSaveEntry>,
+    #[serde(rename = "PasswordEntry", default)]
+    pub passwords: Vec<THPasswordSaveEntry>,
 
     #[serde(rename = "AreaSaveData")]
-    area_save_data: Vec<THAreaSaveData>,
+    pub area_save_data: Vec<THAreaSaveData>,
 
     #[serde(rename = "SecretWorldSaveData")]
-    secret_world_save_data: Option<Vec<THSecretWorldSaveData>>,
+    pub secret_world_save_data: Option<Vec<THSecretWorldSaveData>>,
 
     #[serde(rename = "AutoMap")]
-    auto_maps: Vec<THAutoMapData>,
+    pub auto_maps: Vec<THAutoMapData>,
 
     #[serde(rename = "SpeedrunCheckpoint")]
     pub speedrun_checkpoints: Option<Vec<THSpeedrunCheckpoint>>,
@@ -662,3 +918,84 @@ pub struct THSaveData {
     #[serde(rename = "CreatureGlitched")]
     pub creatures_glitched: Option<Vec<Creature>>,
 }
+
+impl THSaveData {
+    // Returns true if the player has collected the named item.
+    pub fn has_item(&self, name: &str) -> bool {
+        self.items
+            .iter()
+            .any(|item| item.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Number of creatures needed for the Hacker achievement. Adding a creature
+    // means deciding if it counts towards Hacker, then updating this.
+    const HACKER_CREATURES: usize = 59;
+
+    // Creatures in the bestiary that Hacker doesn't need.
+    const NOT_REQUIRED: &[Creature] = &[
+        Creature::Jormite,
+        Creature::TentacleGrass,
+        Creature::RepairDrone,
+    ];
+
+    #[test]
+    fn all_creatures_are_unique() {
+        let unique: HashSet<&Creature> = Creature::ALL.iter().collect();
+
+        assert_eq!(unique.len(), Creature::ALL.len());
+    }
+
+    #[test]
+    fn every_creature_is_classified() {
+        let required = Creature::achievement_list();
+
+        assert_eq!(
+            required.len(),
+            HACKER_CREATURES,
+            "Creatures changed, check Creature::hacker and HACKER_CREATURES",
+        );
+
+        for creature in Creature::ALL {
+            assert_eq!(
+                required.contains(creature),
+                !NOT_REQUIRED.contains(creature),
+                "{:?} is misclassified",
+                creature,
+            );
+        }
+    }
+
+    #[test]
+    fn every_creature_has_a_category() {
+        let count = |category| {
+            Creature::ALL
+                .iter()
+                .filter(|creature| creature.category() == category)
+                .count()
+        };
+
+        assert_eq!(count(CreatureCategory::Fauna), 46);
+        assert_eq!(count(CreatureCategory::Flora), 6);
+        assert_eq!(count(CreatureCategory::Mechanized), 8);
+        assert_eq!(count(CreatureCategory::Other), 2);
+        assert_eq!(count(CreatureCategory::Unknown), 0);
+    }
+
+    #[test]
+    fn creature_ids_round_trip() {
+        for creature in Creature::ALL {
+            assert_eq!(&Creature::from_id(creature.id()), creature);
+        }
+
+        assert_eq!(Creature::from_id("TubeWorm_Meta"), Creature::TubePuff);
+        assert_eq!(
+            Creature::from_id("DragWeed"),
+            Creature::Unknown("DragWeed".to_string()),
+        );
+    }
+}
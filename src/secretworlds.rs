@@ -0,0 +1,178 @@
+// secretworlds: Report on the secret worlds found during the game.
+use crate::items;
+use crate::savedata::{
+    THSaveData,
+    THSecretWorldSaveData,
+};
+use std::str;
+
+pub struct SecretWorlds<'a> {
+    savedata: &'a THSaveData,
+}
+
+impl<'a> SecretWorlds<'a> {
+    pub fn new(savedata: &'a THSaveData) -> Self {
+        Self {
+            savedata,
+        }
+    }
+
+    // Secret worlds sorted by area, then name.
+    fn worlds(&self) -> Vec<&'a THSecretWorldSaveData> {
+        let mut worlds: Vec<&THSecretWorldSaveData> = self.savedata
+            .secret_world_save_data
+            .iter()
+            .flatten()
+            .collect();
+
+        worlds.sort_by(|a, b| {
+            let a_key = (items::area_order(&a.area_name), &a.secret_world_name);
+            let b_key = (items::area_order(&b.area_name), &b.secret_world_name);
+
+            a_key.cmp(&b_key)
+        });
+
+        worlds
+    }
+
+    // Describes an item, noting items that aren't in the catalogue.
+    fn item(&self, id: &str) -> String {
+        let state = if self.savedata.has_item(id) {
+            "Collected"
+        }
+        else {
+            "Not collected"
+        };
+
+        match items::lookup(id) {
+            Some(item) => format!("{} ({})", item.name, state),
+            None       => format!("{} ({}, unknown item)", id, state),
+        }
+    }
+
+    // Items from the secret worlds that haven't been collected yet, along
+    // with the world they're found in.
+    fn needed(
+        &self,
+        worlds: &[&'a THSecretWorldSaveData],
+    ) -> Vec<(&'a THSecretWorldSaveData, &'a str)> {
+        worlds
+            .iter()
+            .flat_map(|world| {
+                [
+                    (*world, world.primary_item.as_str()),
+                    (*world, world.secondary_item.as_str()),
+                ]
+            })
+            .filter(|(_, item)| !self.savedata.has_item(item))
+            .collect()
+    }
+
+    pub fn report(&self) {
+        let worlds = self.worlds();
+
+        println!("Secret Worlds:");
+
+        if worlds.is_empty() {
+            println!("  - No secret worlds visited");
+            return;
+        }
+
+        for world in &worlds {
+            println!("  - {}: {}", world.area_name, world.secret_world_name);
+            println!("    - Primary: {}", self.item(&world.primary_item));
+            println!("    - Secondary: {}", self.item(&world.secondary_item));
+        }
+
+        let needed = self.needed(&worlds);
+
+        println!("Secret World Items Needed:");
+
+        if needed.is_empty() {
+            println!("  - All secret world items collected");
+            return;
+        }
+
+        for (world, item) in needed {
+            println!(
+                "  - {} ({}: {})",
+                items::display_name(item),
+                world.area_name,
+                world.secret_world_name,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata;
+
+    fn save_with_worlds(worlds: &str) -> THSaveData {
+        let xml = testdata::save_with(&[(
+            "<CreatureGlitched>",
+            &format!("{}<CreatureGlitched>", worlds),
+        )]);
+
+        testdata::savedata(&xml)
+    }
+
+    fn world(area: &str, name: &str, primary: &str, secondary: &str) -> String {
+        format!(
+            "<SecretWorldSaveData><mAreaName>{}</mAreaName>\
+             <mSecretWorldName>{}</mSecretWorldName>\
+             <mPrimaryItem>{}</mPrimaryItem>\
+             <mSecondaryItem>{}</mSecondaryItem></SecretWorldSaveData>",
+            area,
+            name,
+            primary,
+            secondary,
+        )
+    }
+
+    #[test]
+    fn lists_uncollected_items() {
+        let game = save_with_worlds(&world(
+            "Eribu",
+            "SecretWorld1",
+            "AxiomDisruptor",
+            "HealthNode",
+        ));
+        let secret_worlds = SecretWorlds::new(&game);
+        let worlds = secret_worlds.worlds();
+        let needed: Vec<(&str, &str)> = secret_worlds.needed(&worlds)
+            .into_iter()
+            .map(|(world, item)| (world.secret_world_name.as_str(), item))
+            .collect();
+
+        assert_eq!(needed, [("SecretWorld1", "HealthNode")]);
+    }
+
+    #[test]
+    fn lists_needed_items_in_area_order() {
+        let worlds = [
+            world("Absu", "SecretWorld2", "PowerNode", "AddressDisruptor"),
+            world("Eribu", "SecretWorld1", "HealthNode", "AxiomDisruptor"),
+        ];
+        let game = save_with_worlds(&worlds.concat());
+        let secret_worlds = SecretWorlds::new(&game);
+        let worlds = secret_worlds.worlds();
+        let needed: Vec<&str> = secret_worlds.needed(&worlds)
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect();
+
+        assert_eq!(needed, ["HealthNode", "PowerNode"]);
+    }
+
+    #[test]
+    fn nothing_needed_without_secret_worlds() {
+        let game = testdata::savedata(testdata::SAVE);
+        let secret_worlds = SecretWorlds::new(&game);
+        let worlds = secret_worlds.worlds();
+
+        assert!(worlds.is_empty());
+        assert!(secret_worlds.needed(&worlds).is_empty());
+    }
+}
@@ -0,0 +1,632 @@
+// steam: Read achievement state from Steam's local stats cache.
+//
+// Steam keeps a copy of each user's stats and achievements in its appcache,
+// so this works without Steam running or a network connection. Two files are
+// needed, both in Steam's binary KeyValues format:
+//
+//   appcache/stats/UserGameStatsSchema_332200.bin
+//     Which stat and bit each achievement is stored in.
+//
+//   appcache/stats/UserGameStats_<account id>_332200.bin
+//     The stat values for a user, one bit per unlocked achievement.
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+use std::convert::{
+    TryFrom,
+    TryInto,
+};
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::str;
+
+// Steam app ID for Axiom Verge.
+const APP_ID: &str = "332200";
+
+// Where the stats files live, relative to the Steam install.
+const STATS_DIR: &str = "appcache/stats";
+
+// Binary KeyValues type markers.
+const TYPE_SECTION: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT32: u8 = 0x02;
+const TYPE_FLOAT32: u8 = 0x03;
+const TYPE_POINTER: u8 = 0x04;
+const TYPE_WIDE_STRING: u8 = 0x05;
+const TYPE_COLOR: u8 = 0x06;
+const TYPE_UINT64: u8 = 0x07;
+const TYPE_END: u8 = 0x08;
+const TYPE_INT64: u8 = 0x0a;
+const TYPE_ALTERNATE_END: u8 = 0x0b;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Section(KeyValues),
+    String(String),
+    Int32(i32),
+    Float32(f32),
+    Pointer(u32),
+    Color(u32),
+    UInt64(u64),
+    Int64(i64),
+}
+
+impl Value {
+    pub fn as_section(&self) -> Option<&KeyValues> {
+        match self {
+            Self::Section(section) => Some(section),
+            _                      => None,
+        }
+    }
+
+    // Integer value of the entry. Steam isn't consistent about which integer
+    // type it uses, and sometimes writes numbers as strings.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Int32(value)   => Some(i64::from(*value)),
+            Self::Pointer(value) => Some(i64::from(*value)),
+            Self::Color(value)   => Some(i64::from(*value)),
+            Self::UInt64(value)  => i64::try_from(*value).ok(),
+            Self::Int64(value)   => Some(*value),
+            Self::String(value)  => value.trim().parse().ok(),
+            _                    => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _                   => None,
+        }
+    }
+}
+
+// A section of key value pairs. Keys aren't unique, and are compared without
+// case, like Steam does.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyValues {
+    pub entries: Vec<(String, Value)>,
+}
+
+impl KeyValues {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut parser = Parser {
+            data,
+            position: 0,
+        };
+
+        // Files are a series of top level entries, usually just one section.
+        parser.section(true)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    pub fn section(&self, key: &str) -> Option<&KeyValues> {
+        self.get(key).and_then(Value::as_section)
+    }
+
+    pub fn integer(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(Value::as_integer)
+    }
+
+    pub fn string(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    // Iterator over the subsections, ignoring any other values.
+    pub fn sections(&self) -> impl Iterator<Item = (&str, &KeyValues)> {
+        self.entries
+            .iter()
+            .filter_map(|(name, value)| {
+                value.as_section().map(|section| (name.as_str(), section))
+            })
+    }
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self.position + length;
+        let bytes = self.data
+            .get(self.position..end)
+            .ok_or_else(|| anyhow!("Unexpected end of KeyValues data"))?;
+
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        // take returns exactly N bytes, safe to unwrap
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn int32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn uint32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn float32(&mut self) -> Result<f32> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn int64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn uint64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    // Null terminated string.
+    fn string(&mut self) -> Result<String> {
+        let rest = &self.data[self.position..];
+        let length = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| anyhow!("Unterminated string in KeyValues data"))?;
+
+        let string = String::from_utf8_lossy(&rest[..length]).into_owned();
+        self.position += length + 1;
+
+        Ok(string)
+    }
+
+    // Null terminated UTF-16 string.
+    fn wide_string(&mut self) -> Result<String> {
+        let mut units = Vec::new();
+
+        loop {
+            match u16::from_le_bytes(self.array()?) {
+                0    => break,
+                unit => units.push(unit),
+            }
+        }
+
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    // Reads entries until the end of the section. The top level has no end
+    // marker, so running out of data ends it too.
+    fn section(&mut self, top_level: bool) -> Result<KeyValues> {
+        let mut section = KeyValues::default();
+
+        loop {
+            if top_level && self.position == self.data.len() {
+                break;
+            }
+
+            let type_ = self.byte()?;
+            if type_ == TYPE_END || type_ == TYPE_ALTERNATE_END {
+                break;
+            }
+
+            let key = self.string()?;
+            let value = match type_ {
+                TYPE_SECTION     => Value::Section(self.section(false)?),
+                TYPE_STRING      => Value::String(self.string()?),
+                TYPE_INT32       => Value::Int32(self.int32()?),
+                TYPE_FLOAT32     => Value::Float32(self.float32()?),
+                TYPE_POINTER     => Value::Pointer(self.uint32()?),
+                TYPE_WIDE_STRING => Value::String(self.wide_string()?),
+                TYPE_COLOR       => Value::Color(self.uint32()?),
+                TYPE_UINT64      => Value::UInt64(self.uint64()?),
+                TYPE_INT64       => Value::Int64(self.int64()?),
+                _                => {
+                    return Err(anyhow!(
+                        "Unknown KeyValues type {:#04x} for {}",
+                        type_,
+                        key,
+                    ));
+                },
+            };
+
+            section.entries.push((key, value));
+        }
+
+        Ok(section)
+    }
+}
+
+// Where an achievement is kept in the user's stats.
+#[derive(Debug)]
+struct AchievementBit {
+    stat: String,
+    bit: u32,
+
+    // English name of the achievement, as shown on Steam.
+    display_name: Option<String>,
+}
+
+// Maps achievement API names to their stat and bit, from the schema file.
+fn parse_schema(schema: &KeyValues) -> Result<HashMap<String, AchievementBit>> {
+    // The schema is a single section named after the app ID.
+    let app = schema
+        .sections()
+        .next()
+        .map(|(_, app)| app)
+        .ok_or_else(|| anyhow!("Stats schema is empty"))?;
+
+    let stats = app
+        .section("stats")
+        .ok_or_else(|| anyhow!("Stats schema has no stats"))?;
+
+    let mut achievements = HashMap::new();
+
+    for (stat, section) in stats.sections() {
+        let bits = match section.section("bits") {
+            Some(bits) => bits,
+            None       => continue,
+        };
+
+        for (index, bit) in bits.sections() {
+            let name = match bit.string("name") {
+                Some(name) => name,
+                None       => continue,
+            };
+
+            let number = bit.integer("bit")
+                .or_else(|| index.parse().ok())
+                .ok_or_else(|| anyhow!("No bit number for {}", name))?;
+
+            // A bit that doesn't fit in the stat's data can never be set.
+            let number = match u32::try_from(number) {
+                Ok(number) if number < i64::BITS => number,
+                _                                => {
+                    eprintln!(
+                        "Warning: Ignoring achievement {}, bit {} is out of \
+                         range",
+                        name,
+                        number,
+                    );
+                    continue;
+                },
+            };
+
+            let display_name = bit
+                .section("display")
+                .and_then(|display| display.section("name"))
+                .and_then(|name| name.string("english"))
+                .map(str::to_string);
+
+            achievements.insert(name.to_string(), AchievementBit {
+                stat: stat.to_string(),
+                bit: number,
+                display_name,
+            });
+        }
+    }
+
+    Ok(achievements)
+}
+
+// Whether the bit is set in a stat's data. Bits past the end aren't set.
+fn is_bit_set(data: i64, bit: u32) -> bool {
+    1i64.checked_shl(bit)
+        .is_some_and(|mask| data & mask != 0)
+}
+
+// Achievements unlocked according to Steam, by API name.
+#[derive(Debug)]
+pub struct SteamAchievements {
+    // Unlock time of each unlocked achievement, in seconds since the Unix
+    // epoch. Steam doesn't always record the time, so it's optional.
+    unlocked: HashMap<String, Option<i64>>,
+
+    // Every achievement in the schema, unlocked or not.
+    known: HashSet<String>,
+
+    // API names of the achievements, by lowercase English name.
+    display_names: HashMap<String, String>,
+
+    // The user stats file these were read from.
+    pub filename: PathBuf,
+}
+
+impl SteamAchievements {
+    // Loads achievements from the Steam install or stats directory given.
+    // A user is needed if more than one user has stats for the game.
+    pub fn load(directory: &str, user: Option<&str>) -> Result<Self> {
+        let directory = stats_directory(Path::new(directory));
+        let schema_file = directory
+            .join(format!("UserGameStatsSchema_{}.bin", APP_ID));
+        let stats_file = user_stats_file(&directory, user)?;
+
+        let schema = read_key_values(&schema_file)?;
+        let stats = read_key_values(&stats_file)?;
+
+        let bits = parse_schema(&schema).with_context(|| {
+            format!("Couldn't read schema {}", schema_file.display())
+        })?;
+
+        // User stats are in a single section, usually named "cache".
+        let cache = stats
+            .sections()
+            .next()
+            .map(|(_, cache)| cache)
+            .ok_or_else(|| {
+                anyhow!("User stats {} are empty", stats_file.display())
+            })?;
+
+        let known = bits.keys().cloned().collect();
+        let display_names = bits
+            .iter()
+            .filter_map(|(name, location)| {
+                location.display_name
+                    .as_ref()
+                    .map(|display| (display.to_lowercase(), name.clone()))
+            })
+            .collect();
+        let mut unlocked = HashMap::new();
+
+        for (name, location) in bits {
+            let stat = match cache.section(&location.stat) {
+                Some(stat) => stat,
+                None       => continue,
+            };
+
+            let data = stat.integer("data").unwrap_or(0);
+            if !is_bit_set(data, location.bit) {
+                continue;
+            }
+
+            let time = stat
+                .section("AchievementTimes")
+                .and_then(|times| times.integer(&location.bit.to_string()))
+                .filter(|&time| time > 0);
+
+            unlocked.insert(name, time);
+        }
+
+        Ok(Self {
+            unlocked,
+            known,
+            display_names,
+            filename: stats_file,
+        })
+    }
+
+    // The API name Steam's schema uses for an achievement. This is the given
+    // API name if the schema has it, otherwise the one with the same English
+    // name, if any.
+    pub fn api_name_for<'a>(
+        &'a self,
+        api_name: &'a str,
+        name: &str,
+    ) -> Option<&'a str> {
+        if self.known.contains(api_name) {
+            return Some(api_name);
+        }
+
+        self.display_names
+            .get(&name.to_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn is_unlocked(&self, api_name: &str) -> bool {
+        self.unlocked.contains_key(api_name)
+    }
+
+    // When the achievement was unlocked, if Steam recorded it.
+    pub fn unlock_time(&self, api_name: &str) -> Option<i64> {
+        self.unlocked.get(api_name).copied().flatten()
+    }
+}
+
+// Accept the Steam install directory as well as the stats directory itself.
+fn stats_directory(directory: &Path) -> PathBuf {
+    let stats = directory.join(STATS_DIR);
+
+    if stats.is_dir() {
+        stats
+    }
+    else {
+        directory.to_path_buf()
+    }
+}
+
+// Finds the user stats file for the game, for the given user if any.
+fn user_stats_file(directory: &Path, user: Option<&str>) -> Result<PathBuf> {
+    let suffix = format!("_{}.bin", APP_ID);
+
+    if let Some(user) = user {
+        return Ok(directory.join(format!("UserGameStats_{}{}", user, suffix)));
+    }
+    let entries = fs::read_dir(directory).with_context(|| {
+        format!("Couldn't read Steam stats in {}", directory.display())
+    })?;
+
+    let mut users: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter_map(|name| {
+            name.strip_prefix("UserGameStats_")
+                .and_then(|name| name.strip_suffix(&suffix))
+                .map(str::to_string)
+        })
+        .collect();
+
+    users.sort();
+
+    match users.as_slice() {
+        [] => {
+            Err(anyhow!(
+                "No Axiom Verge stats found in {}",
+                directory.display(),
+            ))
+        },
+        [user] => {
+            Ok(directory.join(format!("UserGameStats_{}{}", user, suffix)))
+        },
+        _ => {
+            Err(anyhow!(
+                "Stats found for more than one user, pick one with \
+                 --steam-user: {}",
+                users.join(", "),
+            ))
+        },
+    }
+}
+
+fn read_key_values(filename: &Path) -> Result<KeyValues> {
+    let data = fs::read(filename)
+        .with_context(|| format!("Couldn't read {}", filename.display()))?;
+
+    KeyValues::parse(&data)
+        .with_context(|| format!("Couldn't parse {}", filename.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Builds binary KeyValues data for tests.
+    fn entry(type_: u8, key: &str, value: &[u8]) -> Vec<u8> {
+        let mut data = vec![type_];
+        data.extend_from_slice(key.as_bytes());
+        data.push(0);
+        data.extend_from_slice(value);
+        data
+    }
+
+    fn section(key: &str, entries: &[Vec<u8>]) -> Vec<u8> {
+        let mut value: Vec<u8> = entries.concat();
+        value.push(TYPE_END);
+        entry(TYPE_SECTION, key, &value)
+    }
+
+    fn string(key: &str, value: &str) -> Vec<u8> {
+        let mut bytes = value.as_bytes().to_vec();
+        bytes.push(0);
+        entry(TYPE_STRING, key, &bytes)
+    }
+
+    fn int32(key: &str, value: i32) -> Vec<u8> {
+        entry(TYPE_INT32, key, &value.to_le_bytes())
+    }
+
+    #[test]
+    fn parses_nested_sections() {
+        let data = section("root", &[
+            string("name", "ACH_HACK"),
+            int32("bit", 12),
+            section("empty", &[]),
+            entry(TYPE_UINT64, "big", &u64::MAX.to_le_bytes()),
+        ]);
+
+        let root = KeyValues::parse(&data).unwrap();
+        let root = root.section("ROOT").unwrap();
+
+        assert_eq!(root.string("name"), Some("ACH_HACK"));
+        assert_eq!(root.integer("bit"), Some(12));
+        assert_eq!(root.section("empty"), Some(&KeyValues::default()));
+        assert_eq!(root.get("big"), Some(&Value::UInt64(u64::MAX)));
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let mut data = section("root", &[int32("bit", 12)]);
+        data.truncate(data.len() - 3);
+
+        assert!(KeyValues::parse(&data).is_err());
+    }
+
+    #[test]
+    fn maps_schema_bits_to_names() {
+        let data = section(APP_ID, &[
+            section("stats", &[
+                section("1", &[
+                    int32("type", 4),
+                    section("bits", &[
+                        section("0", &[
+                            string("name", "ACH_HACK"),
+                            int32("bit", 0),
+                            section("display", &[
+                                section("name", &[
+                                    string("english", "Hack"),
+                                ]),
+                            ]),
+                        ]),
+                        section("5", &[
+                            string("name", "ACH_XEDUR"),
+                        ]),
+                        section("6", &[
+                            string("name", "ACH_NEGATIVE"),
+                            int32("bit", -1),
+                        ]),
+                        section("7", &[
+                            string("name", "ACH_TOO_BIG"),
+                            int32("bit", 64),
+                        ]),
+                    ]),
+                ]),
+            ]),
+        ]);
+
+        let schema = KeyValues::parse(&data).unwrap();
+        let bits = parse_schema(&schema).unwrap();
+
+        assert_eq!(bits["ACH_HACK"].stat, "1");
+        assert_eq!(bits["ACH_HACK"].bit, 0);
+        assert_eq!(bits["ACH_HACK"].display_name.as_deref(), Some("Hack"));
+        assert_eq!(bits["ACH_XEDUR"].bit, 5);
+        assert_eq!(bits["ACH_XEDUR"].display_name, None);
+        assert!(!bits.contains_key("ACH_NEGATIVE"));
+        assert!(!bits.contains_key("ACH_TOO_BIG"));
+    }
+
+    #[test]
+    fn checks_bits_in_range() {
+        assert!(is_bit_set(0b100, 2));
+        assert!(!is_bit_set(0b100, 1));
+        assert!(is_bit_set(i64::MIN, 63));
+        assert!(!is_bit_set(-1, 64));
+        assert!(!is_bit_set(-1, u32::MAX));
+    }
+
+    #[test]
+    fn matches_api_names_by_english_name() {
+        let mut known = HashSet::new();
+        known.insert("HACK".to_string());
+        known.insert("ACH_XEDUR".to_string());
+
+        let mut display_names = HashMap::new();
+        display_names.insert("hack".to_string(), "HACK".to_string());
+
+        let steam = SteamAchievements {
+            unlocked: HashMap::new(),
+            known,
+            display_names,
+            filename: PathBuf::new(),
+        };
+
+        assert_eq!(steam.api_name_for("ACH_XEDUR", "Xedur"), Some("ACH_XEDUR"));
+        assert_eq!(steam.api_name_for("ACH_HACK", "Hack"), Some("HACK"));
+        assert_eq!(steam.api_name_for("ACH_TELAL", "Telal"), None);
+    }
+}
@@ -0,0 +1,56 @@
+// testdata: A small save game for tests to build on.
+use crate::savedata::THSaveData;
+use serde_xml_rs::from_reader;
+
+// A normal difficulty save, part way through the game with Xedur defeated.
+pub const SAVE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<THSaveData xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+  <mScreenSize>2</mScreenSize>
+  <mPlayerName>Trace</mPlayerName>
+  <mDifficulty>NORMAL</mDifficulty>
+  <mCurrentWeapon>AxiomDisruptor</mCurrentWeapon>
+  <mSaveArea>Eribu</mSaveArea>
+  <mSaveRoom>Room12</mSaveRoom>
+  <mSaveRoomPos><X>10</X><Y>4</Y></mSaveRoomPos>
+  <mTotalFrames>432000</mTotalFrames>
+  <mEffectiveFrames>400000</mEffectiveFrames>
+  <mScreenCount>120</mScreenCount>
+  <mTotalScreenCount>600</mTotalScreenCount>
+  <mNumDeaths>1</mNumDeaths>
+  <mRedGooDestroyed>300</mRedGooDestroyed>
+  <mBricksDestroyed>2500</mBricksDestroyed>
+  <mIsSpeedRun>false</mIsSpeedRun>
+  <mUseRealTimers>false</mUseRealTimers>
+  <mLastMapSubScreen>MAP</mLastMapSubScreen>
+  <mBaseSeed>12345</mBaseSeed>
+  <mBiofluxVisions>false</mBiofluxVisions>
+  <mHallucinationAmount>0</mHallucinationAmount>
+  <mTranslatePrimordial>false</mTranslatePrimordial>
+  <mTranslateVykhya>false</mTranslateVykhya>
+  <mJustinBailey>false</mJustinBailey>
+  <mHasDrone>false</mHasDrone>
+  <mCheatsUsed>false</mCheatsUsed>
+  <QuickSelectWeapon>AxiomDisruptor</QuickSelectWeapon>
+  <THItemRecord><mName>AxiomDisruptor</mName><mType>WEAPON</mType><mConsumable>false</mConsumable><mExcludedFromCount>false</mExcludedFromCount></THItemRecord>
+  <THItemRecord><mName>AddressDisruptor</mName><mType>TOOL</mType><mConsumable>false</mConsumable><mExcludedFromCount>false</mExcludedFromCount></THItemRecord>
+  <KeyPoint>GotAxiomDisruptor</KeyPoint>
+  <KeyPoint>XedurDefeated</KeyPoint>
+  <AreaSaveData><mAreaName>Eribu</mAreaName><mSeed>111</mSeed><mScreenCount>60</mScreenCount><mX>0</mX><mY>0</mY><mItem>AxiomDisruptor</mItem></AreaSaveData>
+  <AutoMap><mAreaName>Eribu</mAreaName><mWidthScreens>10</mWidthScreens><mHeightScreens>10</mHeightScreens><mScreenCount>60</mScreenCount><mCSVData>0,1,2</mCSVData><Reminder><X>1</X><Y>2</Y></Reminder></AutoMap>
+  <CreatureGlitched>TrapClaw_Meta</CreatureGlitched>
+</THSaveData>
+"#;
+
+// The test save, with each `(from, to)` replacement applied to the XML.
+pub fn save_with(replacements: &[(&str, &str)]) -> String {
+    replacements
+        .iter()
+        .fold(SAVE.to_string(), |xml, (from, to)| {
+            assert!(xml.contains(from), "Test save doesn't contain {}", from);
+            xml.replace(from, to)
+        })
+}
+
+pub fn savedata(xml: &str) -> THSaveData {
+    from_reader(xml.as_bytes()).unwrap()
+}
@@ -0,0 +1,332 @@
+// verify: Check that a save game is something the game will load.
+use crate::crypto::{
+    DecryptReader,
+    BLOCK_SIZE,
+};
+use crate::document::Document;
+use crate::profiles::KeySelection;
+use crate::savedata::{
+    Creature,
+    THSaveData,
+};
+use serde_xml_rs::from_reader;
+use std::collections::HashSet;
+use std::io::prelude::*;
+use std::str;
+
+// Problems found in a save game. Warnings are things worth knowing about, but
+// that the game copes with.
+pub struct Verify {
+    problems: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl Verify {
+    // Verify the given save data. Keys are given if the data is encrypted.
+    pub fn new(data: &[u8], keys: Option<&KeySelection>) -> Self {
+        let mut verify = Self {
+            problems: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        verify.check(data, keys);
+
+        verify
+    }
+
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    fn problem(&mut self, problem: String) {
+        self.problems.push(problem);
+    }
+
+    fn warning(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    // Run the checks in order. Each stage depends on the previous one, so we
+    // stop at the first stage that fails.
+    fn check(&mut self, data: &[u8], keys: Option<&KeySelection>) {
+        let decrypted;
+        let data = match keys {
+            Some(keys) => {
+                decrypted = match self.decrypt(data, keys) {
+                    Some(decrypted) => decrypted,
+                    None            => return,
+                };
+
+                &decrypted
+            },
+            None => data,
+        };
+
+        let text = match str::from_utf8(data) {
+            Ok(text) => text,
+            Err(e)   => {
+                self.problem(format!("Save data is not valid UTF-8: {}", e));
+                return;
+            },
+        };
+
+        if let Err(e) = Document::parse(text.as_bytes()) {
+            self.problem(format!("Save data is not valid XML: {}", e));
+            return;
+        }
+
+        let savedata: THSaveData = match from_reader(text.as_bytes()) {
+            Ok(savedata) => savedata,
+            Err(e)       => {
+                self.problem(format!("Save data doesn't parse: {}", e));
+                return;
+            },
+        };
+
+        self.check_creature_ids(&savedata);
+        self.check_ranges(&savedata);
+        self.check_duplicates(&savedata);
+    }
+
+    fn decrypt(
+        &mut self,
+        data: &[u8],
+        keys: &KeySelection,
+    ) -> Option<Vec<u8>> {
+        if data.is_empty() || !data.len().is_multiple_of(BLOCK_SIZE) {
+            self.problem(format!(
+                "Ciphertext length {} is not a multiple of {}",
+                data.len(),
+                BLOCK_SIZE,
+            ));
+
+            return None;
+        }
+
+        let profile = match keys.profile_for(data) {
+            Some(profile) => profile,
+            None          => {
+                let msg = "No key profile could decrypt the save data";
+                self.problem(msg.to_string());
+                return None;
+            },
+        };
+
+        let mut reader = DecryptReader::new(data, profile);
+        let mut decrypted = Vec::new();
+
+        if let Err(e) = reader.read_to_end(&mut decrypted) {
+            self.problem(format!(
+                "Couldn't decrypt with the {} profile: {}",
+                profile.name,
+                e,
+            ));
+
+            return None;
+        }
+
+        Some(decrypted)
+    }
+
+    // Saves can contain creatures that we don't know about, such as those
+    // from newer versions of the game, so these are only worth a warning.
+    fn check_creature_ids(&mut self, savedata: &THSaveData) {
+        for creature in savedata.creatures_glitched.iter().flatten() {
+            if let Creature::Unknown(id) = creature {
+                self.warning(format!("Unknown creature: {}", id));
+            }
+        }
+    }
+
+    fn check_ranges(&mut self, savedata: &THSaveData) {
+        if savedata.screen_count < 0
+            || savedata.screen_count > savedata.total_screen_count
+        {
+            self.problem(format!(
+                "Screen count {} is outside of 0-{}",
+                savedata.screen_count,
+                savedata.total_screen_count,
+            ));
+        }
+
+        let counters = &[
+            ("Deaths", savedata.num_deaths),
+            ("Bricks destroyed", savedata.bricks_destroyed),
+            ("Red goo destroyed", savedata.red_goo_destroyed),
+        ];
+
+        for (name, value) in counters {
+            if *value < 0 {
+                self.problem(format!("{} is negative: {}", name, value));
+            }
+        }
+
+        if savedata.effective_frames < 0.0 {
+            self.problem(format!(
+                "Effective frames is negative: {}",
+                savedata.effective_frames,
+            ));
+        }
+    }
+
+    fn check_duplicates(&mut self, savedata: &THSaveData) {
+        let mut seen = HashSet::new();
+
+        for item in &savedata.items {
+            if !seen.insert(&item.name) {
+                self.problem(format!("Duplicate item: {}", item.name));
+            }
+        }
+
+        let mut seen = HashSet::new();
+
+        for creature in savedata.creatures_glitched.iter().flatten() {
+            if !seen.insert(creature) {
+                self.problem(format!(
+                    "Duplicate glitched creature: {}",
+                    creature.id(),
+                ));
+            }
+        }
+    }
+
+    pub fn report(&self, filename: &str) {
+        println!("Verifying {}:", filename);
+
+        if self.problems.is_empty() {
+            println!("  - No problems found");
+        }
+
+        for problem in &self.problems {
+            println!("  - {}", problem);
+        }
+
+        for warning in &self.warnings {
+            println!("  - Warning: {}", warning);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{
+        EncryptWriter,
+        KeyProfile,
+    };
+    use crate::testdata::{
+        save_with,
+        SAVE,
+    };
+
+    fn verify(xml: &str) -> Verify {
+        Verify::new(xml.as_bytes(), None)
+    }
+
+    fn encrypt(data: &[u8]) -> Vec<u8> {
+        let mut writer = EncryptWriter::new(Vec::new(), &KeyProfile::steam());
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn steam() -> KeySelection {
+        KeySelection::Profile(KeyProfile::steam())
+    }
+
+    #[test]
+    fn accepts_valid_save() {
+        let verify = verify(SAVE);
+
+        assert_eq!(verify.problems(), &[] as &[String]);
+        assert!(verify.warnings.is_empty());
+    }
+
+    #[test]
+    fn accepts_valid_encrypted_save() {
+        let verify = Verify::new(&encrypt(SAVE.as_bytes()), Some(&steam()));
+
+        assert_eq!(verify.problems(), &[] as &[String]);
+    }
+
+    #[test]
+    fn rejects_truncated_ciphertext() {
+        let data = encrypt(SAVE.as_bytes());
+        let verify = Verify::new(&data[..data.len() - 1], Some(&steam()));
+
+        assert_eq!(verify.problems().len(), 1);
+        assert!(verify.problems()[0].starts_with("Ciphertext length"));
+    }
+
+    #[test]
+    fn rejects_wrong_key() {
+        let wrong = KeyProfile::new("wrong", [0; 16], [0; 16]);
+        let data = encrypt(SAVE.as_bytes());
+        let verify = Verify::new(&data, Some(&KeySelection::Profile(wrong)));
+
+        assert_eq!(verify.problems().len(), 1);
+        assert!(verify.problems()[0].starts_with("Couldn't decrypt"));
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        let xml = save_with(&[("</THSaveData>", "")]);
+        let verify = verify(&xml);
+
+        assert_eq!(verify.problems().len(), 1);
+        assert!(verify.problems()[0].starts_with("Save data is not valid"));
+    }
+
+    #[test]
+    fn rejects_unparseable_save() {
+        let xml = save_with(&[("<mNumDeaths>1</mNumDeaths>", "")]);
+        let verify = verify(&xml);
+
+        assert_eq!(verify.problems().len(), 1);
+        assert!(verify.problems()[0].starts_with("Save data doesn't parse"));
+    }
+
+    #[test]
+    fn reports_every_out_of_range_value() {
+        let xml = save_with(&[
+            (
+                "<mScreenCount>120</mScreenCount>",
+                "<mScreenCount>601</mScreenCount>",
+            ),
+            ("<mNumDeaths>1</mNumDeaths>", "<mNumDeaths>-1</mNumDeaths>"),
+            (
+                "<mEffectiveFrames>400000</mEffectiveFrames>",
+                "<mEffectiveFrames>-1</mEffectiveFrames>",
+            ),
+        ]);
+        let verify = verify(&xml);
+
+        assert_eq!(verify.problems(), [
+            "Screen count 601 is outside of 0-600",
+            "Deaths is negative: -1",
+            "Effective frames is negative: -1",
+        ]);
+    }
+
+    #[test]
+    fn reports_duplicates() {
+        let creature = "<CreatureGlitched>TrapClaw_Meta</CreatureGlitched>";
+        let xml = save_with(&[(creature, &creature.repeat(2))]);
+        let verify = verify(&xml);
+
+        assert_eq!(verify.problems(), [
+            "Duplicate glitched creature: TrapClaw_Meta",
+        ]);
+    }
+
+    #[test]
+    fn unknown_creatures_are_warnings() {
+        let xml = save_with(&[(
+            "<CreatureGlitched>TrapClaw_Meta</CreatureGlitched>",
+            "<CreatureGlitched>NewCreature</CreatureGlitched>",
+        )]);
+        let verify = verify(&xml);
+
+        assert_eq!(verify.problems(), &[] as &[String]);
+        assert_eq!(verify.warnings, ["Unknown creature: NewCreature"]);
+    }
+}